@@ -0,0 +1,250 @@
+mod test;
+
+use crate::options;
+use crate::sink;
+use crate::source;
+
+use options::Options;
+use sink::{SearchStats, Sink, SinkLine, SourceStats};
+use source::Source;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fs;
+use std::io::{stderr, Write};
+
+/// First line of baseline files.
+const BASELINE_HEADER: &str = "# greprs baseline";
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+const FNV_PRIME: u64 = 0x100000001b3;
+
+/// Filters matches that are recorded in a baseline before passing lines on to another sink.
+///
+/// Matches are identified by a [fingerprint] of their source path, line content and
+/// matched text, so they are still recognized after lines have moved. Lines without any
+/// new matches are dropped and statistics of sources only count new matches. Every
+/// match is recorded so a new baseline can be written with [write_baseline](BaselineFilter::write_baseline).
+pub struct BaselineFilter<'a> {
+    /// Sink receiving lines with new matches.
+    inner: &'a mut dyn Sink,
+    /// Fingerprints of the baseline, if one is used.
+    known: Option<HashSet<String>>,
+    /// Fingerprints and paths of every match in search order.
+    recorded: Vec<(String, String)>,
+    /// Number of times each line content and matched text was seen in the current source.
+    occurrences: HashMap<(String, String), usize>,
+    /// Number of matches that are not in the baseline.
+    new: usize,
+    /// Selected lines and matches of the current source that were dropped.
+    dropped: SourceStats,
+    /// If the last recorded match is new, decides if spans continuing it are kept.
+    continued_match_new: bool,
+}
+
+impl<'a> BaselineFilter<'a> {
+    /// Creates a filter passing lines on to `inner`. Matches are filtered with
+    /// the baseline read from `baseline_path`, if any.
+    pub fn new(inner: &'a mut dyn Sink, baseline_path: Option<&str>) -> Result<BaselineFilter<'a>, Box<dyn Error>> {
+        let known = match baseline_path {
+            Some(path) => Some(read_baseline(path)?),
+            None => None,
+        };
+
+        Ok(BaselineFilter {
+            inner,
+            known,
+            recorded: Vec::new(),
+            occurrences: HashMap::new(),
+            new: 0,
+            dropped: SourceStats::default(),
+            continued_match_new: false,
+        })
+    }
+
+    /// Writes the fingerprints of every match that was seen to `path`, one match per line.
+    pub fn write_baseline(&self, path: &str) -> Result<(), Box<dyn Error>> {
+        let mut contents = String::from(BASELINE_HEADER);
+        contents.push('\n');
+
+        for (fingerprint, source_path) in &self.recorded {
+            contents.push_str(&format!("{}\t{}\n", fingerprint, source_path));
+        }
+
+        fs::write(path, contents)
+            .map_err(|io_err| format!("Could not write baseline {}: {}", path, io_err))?;
+
+        Ok(())
+    }
+
+    /// Returns the number of matches that are not in the baseline.
+    pub fn new_matches(&self) -> usize {
+        self.new
+    }
+
+    /// Returns the number of baseline matches that were not seen.
+    pub fn fixed_matches(&self) -> usize {
+        let Some(known) = &self.known else {
+            return 0;
+        };
+
+        let seen: HashSet<&String> = self.recorded.iter().map(|(fingerprint, _)| fingerprint).collect();
+
+        known.iter().filter(|fingerprint| !seen.contains(fingerprint)).count()
+    }
+
+    /// Records the fingerprint of a match, returns `true` if it is new.
+    fn record(&mut self, path: &str, line: &str, match_text: &str) -> bool {
+        let key = (String::from(line.trim()), String::from(match_text));
+        let occurrence = self.occurrences.entry(key).or_insert(0);
+        *occurrence += 1;
+
+        let fingerprint = fingerprint(path, line, match_text, *occurrence);
+        let is_new = self.known.as_ref().is_none_or(|known| !known.contains(&fingerprint));
+
+        self.recorded.push((fingerprint, String::from(path)));
+
+        if is_new {
+            self.new += 1;
+        }
+
+        is_new
+    }
+}
+
+impl Sink for BaselineFilter<'_> {
+    fn begin_source(&mut self, options: &Options, source: &Source) {
+        self.occurrences.clear();
+        self.dropped = SourceStats::default();
+        self.continued_match_new = false;
+        self.inner.begin_source(options, source);
+    }
+
+    /// Passes the line on with only its new matches. Lines selected without
+    /// matches when inverting are treated as a single match. Multiline matches
+    /// are fingerprinted by the span that starts them, spans continuing them
+    /// are kept or dropped along with that span.
+    fn matched_line(&mut self, options: &Options, source: &Source, line: &SinkLine) {
+        if line.matches.is_empty() {
+            // without inverting only lines inside of a multiline match have no matches
+            let is_new = if options.invert_match {
+                self.record(&source.path, line.text, "")
+            } else {
+                self.continued_match_new
+            };
+
+            if is_new {
+                self.inner.matched_line(options, source, line);
+            } else {
+                self.dropped.matched_lines += 1;
+            }
+
+            return;
+        }
+
+        let mut filtered_line = SinkLine {
+            matches: Vec::new(),
+            replacements: Vec::new(),
+            continues_match: false,
+            match_patterns: Vec::new(),
+            ..line.clone()
+        };
+
+        for (index, span) in line.matches.iter().enumerate() {
+            let is_new = if index == 0 && line.continues_match {
+                filtered_line.continues_match = self.continued_match_new;
+                self.continued_match_new
+            } else {
+                let is_new = self.record(&source.path, line.text, &line.text[span.start..span.end]);
+                if !is_new {
+                    self.dropped.matches += 1;
+                }
+
+                self.continued_match_new = is_new;
+                is_new
+            };
+
+            if is_new {
+                filtered_line.matches.push(*span);
+
+                if let Some(replacement) = line.replacements.get(index) {
+                    filtered_line.replacements.push(replacement.clone());
+                }
+
+                if let Some(pattern) = line.match_patterns.get(index) {
+                    filtered_line.match_patterns.push(*pattern);
+                }
+            }
+        }
+
+        if filtered_line.matches.is_empty() {
+            self.dropped.matched_lines += 1;
+        } else {
+            self.inner.matched_line(options, source, &filtered_line);
+        }
+    }
+
+    fn context_line(&mut self, options: &Options, source: &Source, line: &SinkLine) {
+        self.inner.context_line(options, source, line);
+    }
+
+    /// Passes statistics on without the dropped lines and matches.
+    fn end_source(&mut self, options: &Options, source: &Source, stats: &SourceStats) {
+        let stats = SourceStats {
+            matched_lines: stats.matched_lines.saturating_sub(self.dropped.matched_lines),
+            matches: stats.matches.saturating_sub(self.dropped.matches),
+            ..*stats
+        };
+
+        self.inner.end_source(options, source, &stats);
+    }
+
+    fn report_stats(&mut self, options: &Options, stats: &SearchStats) -> bool {
+        self.inner.report_stats(options, stats)
+    }
+
+    /// Finishes the inner sink, then writes the number of new and fixed matches
+    /// to stderr when a baseline is used.
+    fn finish(&mut self, options: &Options) {
+        self.inner.finish(options);
+
+        if self.known.is_some() && !options.silent {
+            writeln!(
+                stderr(),
+                "grepRS: baseline: {} new, {} fixed",
+                self.new_matches(),
+                self.fixed_matches()
+            ).expect("grepRS: could not write to stderr!");
+        }
+    }
+}
+
+/// Builds the fingerprint of a match from the path of its source, the content of its
+/// line without surrounding whitespace, the matched text and the number of times the
+/// same line content and match occurred in the source so far, starting from 1.
+/// Line numbers are not included so fingerprints don't change when lines move.
+pub fn fingerprint(path: &str, line: &str, match_text: &str, occurrence: usize) -> String {
+    let mut hash = FNV_OFFSET_BASIS;
+
+    let occurrence = occurrence.to_string();
+    for part in [path, line.trim(), match_text, &occurrence] {
+        for byte in part.bytes().chain([0]) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    format!("{:016x}", hash)
+}
+
+/// Reads the fingerprints of a baseline file written by
+/// [write_baseline](BaselineFilter::write_baseline).
+pub fn read_baseline(path: &str) -> Result<HashSet<String>, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|io_err| format!("Could not read baseline {}: {}", path, io_err))?;
+
+    let fingerprints = contents.lines()
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| String::from(line.split('\t').next().unwrap_or(line)))
+        .collect();
+
+    Ok(fingerprints)
+}
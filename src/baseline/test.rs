@@ -0,0 +1,125 @@
+#![cfg(test)]
+#![allow(unused_imports)]
+use regex::Regex;
+
+use crate::matcher;
+use crate::sink::EventCollector;
+
+use super::*;
+
+#[test]
+fn fingerprints_ignore_indentation() {
+    let fingerprint_a = fingerprint("a.rs", "    x.unwrap();", "unwrap", 1);
+
+    assert_eq!(fingerprint_a.len(), 16);
+    assert_eq!(fingerprint_a, fingerprint("a.rs", "x.unwrap();\t", "unwrap", 1));
+    assert_ne!(fingerprint_a, fingerprint("b.rs", "x.unwrap();", "unwrap", 1));
+    assert_ne!(fingerprint_a, fingerprint("a.rs", "x.unwrap();", "unwrap", 2));
+    assert_ne!(fingerprint_a, fingerprint("a.rs", "y.unwrap();", "unwrap", 1));
+}
+
+#[test]
+fn filters_matches_in_baseline() {
+    let baseline_path = std::env::temp_dir().join(format!("greprs-baseline-{}.txt", std::process::id()));
+    let baseline_path = baseline_path.to_str().unwrap();
+
+    let options = Options {
+        patterns: vec!(String::from("yet")),
+        ..Default::default()
+    };
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    let old_source = Source {
+        path: String::from("notes.txt"),
+        data: String::from("and yet\nstill yet, yet\ngone yet\n"),
+        bytes: None,
+    };
+
+    let mut collector = EventCollector::default();
+    let mut filter = BaselineFilter::new(&mut collector, None).unwrap();
+    matcher::search_source(&regex, &options, &old_source, &mut filter);
+    assert_eq!(filter.new_matches(), 4);
+    filter.write_baseline(baseline_path).unwrap();
+
+    // lines moved, one match was added and one fixed
+    let new_source = Source {
+        path: String::from("notes.txt"),
+        data: String::from("header\nstill yet, yet\nand yet\nand yet\n"),
+        bytes: None,
+    };
+
+    let mut collector = EventCollector::default();
+    let mut filter = BaselineFilter::new(&mut collector, Some(baseline_path)).unwrap();
+    matcher::search_source(&regex, &options, &new_source, &mut filter);
+
+    assert_eq!(filter.new_matches(), 1);
+    assert_eq!(filter.fixed_matches(), 1);
+    assert_eq!(collector.events, vec!(
+        String::from("begin notes.txt"),
+        String::from("match 4 30 and yet [4..7]"),
+        String::from("end notes.txt 1 1 38"),
+    ));
+
+    std::fs::remove_file(baseline_path).unwrap();
+}
+
+#[test]
+fn reads_baseline_files() {
+    assert!(read_baseline("does/not/exist").is_err());
+}
+
+#[test]
+fn filters_multiline_matches_in_baseline() {
+    let baseline_path = std::env::temp_dir().join(format!("greprs-baseline-multiline-{}.txt", std::process::id()));
+    let baseline_path = baseline_path.to_str().unwrap();
+
+    let options = Options {
+        patterns: vec!(String::from(r"foo\(\n[^)]*\)")),
+        multiline: true,
+        ..Default::default()
+    };
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    let old_source = Source {
+        path: String::from("f.rs"),
+        data: String::from("foo(\n  a,\n\n  b)\n"),
+        bytes: None,
+    };
+
+    let mut collector = EventCollector::default();
+    let mut filter = BaselineFilter::new(&mut collector, None).unwrap();
+    matcher::search_source(&regex, &options, &old_source, &mut filter);
+    assert_eq!(filter.new_matches(), 1);
+    filter.write_baseline(baseline_path).unwrap();
+
+    assert_eq!(read_baseline(baseline_path).unwrap().len(), 1);
+
+    let mut collector = EventCollector::default();
+    let mut filter = BaselineFilter::new(&mut collector, Some(baseline_path)).unwrap();
+    matcher::search_source(&regex, &options, &old_source, &mut filter);
+
+    assert_eq!(filter.new_matches(), 0);
+    assert_eq!(filter.fixed_matches(), 0);
+    assert_eq!(collector.events, vec!(String::from("begin f.rs"), String::from("end f.rs 0 0 16")));
+
+    // a second match is new, the known match is still dropped
+    let new_source = Source {
+        path: String::from("f.rs"),
+        data: String::from("foo(\n  a,\n\n  b)\nfoo(\n  c)\n"),
+        bytes: None,
+    };
+
+    let mut collector = EventCollector::default();
+    let mut filter = BaselineFilter::new(&mut collector, Some(baseline_path)).unwrap();
+    matcher::search_source(&regex, &options, &new_source, &mut filter);
+
+    assert_eq!(filter.new_matches(), 1);
+    assert_eq!(collector.events, vec!(
+        String::from("begin f.rs"),
+        String::from("match 5 16 foo( [0..4]"),
+        String::from("match 6 21   c) [0..4]"),
+        String::from("end f.rs 2 1 26"),
+    ));
+
+    std::fs::remove_file(baseline_path).unwrap();
+}
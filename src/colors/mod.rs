@@ -2,6 +2,15 @@ mod test;
 
 use std::env::var_os;
 
+/// Environment variables colors are read from, in order of precedence.
+const COLORS_VARS: [&str; 2] = ["GREPRS_COLORS", "GREP_COLORS"];
+/// Names of colors in the order of their ANSI color codes.
+const COLOR_NAMES: [&str; 8] = ["black", "red", "green", "yellow", "blue", "magenta", "cyan", "white"];
+/// Names of built-in themes, see [Colors::theme].
+pub const THEMES: [&str; 5] = ["default", "dark", "light", "colorblind", "monochrome"];
+/// Colors cycled through for matches of each pattern with `--pattern-colors`.
+pub const PATTERN_PALETTE: [&str; 6] = ["1;31", "1;32", "1;33", "1;34", "1;35", "1;36"];
+
 /// Defines the ANSI escape codes that should be used for output highlights.
 #[derive(PartialEq, Eq, Debug, Clone)]
 pub struct Colors {
@@ -23,76 +32,278 @@ pub struct Colors {
     pub byte_offset: String,
     /// Defines the formatting of separators used in output. Defaults to magenta.
     pub separator: String,
+    /// If `true`, the meanings of `selected_line` and `context_line` are swapped
+    /// when matching is inverted. Set by the `rv` capability. Defaults to `false`.
+    pub reverse: bool,
+    /// If `true`, colored text is not followed by an erase to end of line sequence.
+    /// Set by the `ne` capability. Defaults to `false`.
+    pub no_erase: bool,
 }
 
-impl Colors {
-    /// Gets the colors to be used for a run. Looks for an environment variable
-    /// named 'GREPRS_COLORS' and falls back to default values if no environment variable
-    /// is found.
-    pub fn get_colors() -> Colors {
-        let colors_var = var_os("GREPRS_COLORS");
-
-        return match colors_var {
-            None => Self::default(),
-            Some(colors) => {
-                let colors = colors.into_string();
-
-                if let Ok(color_str) = colors {
-                    Self::parse_colors(color_str)
-                } else {
-                    Self::default()
-                }
-            }
-        }
-    }
-
+impl Default for Colors {
     /// Builds default GREPRS Colors struct.
-    pub fn default() -> Colors {
+    fn default() -> Colors {
         Colors {
             selected_match: String::from("1;33"), // bold yellow
-            context_match: String::from("1:33"), // bold yellow
+            context_match: String::from("1;33"), // bold yellow
             selected_line: String::from(""), // device default
             context_line: String::from(""), // device default
             file_name: String::from("32"), // green
             line_number: String::from("31"), // red
             byte_offset: String::from("36"), // cyan
             separator: String::from("35"), // magenta
+            reverse: false,
+            no_erase: false,
         }
     }
+}
 
-    /// Constructs a Colors struct from a environment string variable.
-    /// Uses defaults for any malformed or missing options.
-    fn parse_colors(colors_str: String) -> Colors {
-        let mut colors = Self::default();
+impl Colors {
+    /// Gets the colors to be used for a run. Looks for an environment variable
+    /// named 'GREPRS_COLORS', then for GNU grep's 'GREP_COLORS', and falls back to
+    /// default values if neither environment variable is found.
+    pub fn get_colors() -> Colors {
+        Self::get_themed_colors(Self::default())
+    }
+
+    /// Gets the colors to be used for a run, starting from `theme`. Colors set by
+    /// an environment variable take precedence over those of the theme.
+    /// See [get_colors](Colors::get_colors).
+    pub fn get_themed_colors(theme: Colors) -> Colors {
+        match Self::colors_var() {
+            Some(color_str) => Self::parse_colors_onto(theme, color_str),
+            None => theme,
+        }
+    }
 
+    /// Builds the colors of a built-in theme, see [THEMES]. Returns `None` for
+    /// unknown theme names.
+    /// *   `default`: colors used by GNU grep.
+    /// *   `dark`: brighter colors for dark backgrounds.
+    /// *   `light`: darker colors that stay readable on light backgrounds.
+    /// *   `colorblind`: orange and blue colors that avoid red and green.
+    /// *   `monochrome`: no colors, matches are bold and reversed.
+    pub fn theme(name: &str) -> Option<Colors> {
+        let theme = |matches: &str, file_name: &str, line_number: &str, byte_offset: &str, separator: &str| {
+            Colors {
+                selected_match: String::from(matches),
+                context_match: String::from(matches),
+                file_name: String::from(file_name),
+                line_number: String::from(line_number),
+                byte_offset: String::from(byte_offset),
+                separator: String::from(separator),
+                ..Self::default()
+            }
+        };
+
+        match name {
+            "default" => Some(Self::default()),
+            "dark" => Some(theme("1;93", "92", "91", "96", "95")),
+            "light" => Some(theme("1;31", "34", "35", "36", "90")),
+            "colorblind" => Some(theme("1;38;5;208", "38;5;33", "38;5;178", "38;5;37", "38;5;244")),
+            "monochrome" => Some(theme("1;7", "1", "", "", "")),
+            _ => None,
+        }
+    }
+
+    /// Gets messages describing malformed entries of the colors environment
+    /// variable used by [get_colors](Colors::get_colors).
+    pub fn get_color_warnings() -> Vec<String> {
+        match Self::colors_var() {
+            Some(color_str) => Self::validate_colors(&color_str),
+            None => Vec::new(),
+        }
+    }
+
+    /// Reads the first colors environment variable that is set.
+    /// Variables that aren't valid unicode are ignored.
+    fn colors_var() -> Option<String> {
+        COLORS_VARS.iter()
+            .find_map(var_os)
+            .and_then(|colors| colors.into_string().ok())
+    }
+
+    /// Applies the options of a colors string to `colors` using the grammar of GNU
+    /// grep's GREP_COLORS, see [parse_color_spec](Colors::parse_color_spec) for the
+    /// accepted color values. Malformed or missing options keep their value in `colors`.
+    fn parse_colors_onto(mut colors: Colors, colors_str: String) -> Colors {
         if !colors_str.is_ascii() {
             return colors;
         }
 
-        let color_options = colors_str.split(":");
+        let color_options = colors_str.split(':');
 
-        for option in color_options.into_iter() {
-            if option.is_ascii() && option.len() > 3 {
-                let ansi_code = String::from(&option[3..]);
+        for option in color_options {
+            match option {
+                "rv" => colors.reverse = true,
+                "ne" => colors.no_erase = true,
+                _ => {
+                    let Some((capability, spec)) = option.split_once('=') else {
+                        continue;
+                    };
 
-                match &option[..2] {
-                    "mt" => {
-                        colors.selected_match = ansi_code.clone();
-                        colors.context_match = ansi_code;
-                    },
-                    "ms" => colors.selected_match = ansi_code,
-                    "mc" => colors.context_match = ansi_code,
-                    "sl" => colors.selected_line = ansi_code,
-                    "cx" => colors.context_line = ansi_code,
-                    "fn" => colors.file_name = ansi_code,
-                    "ln" => colors.line_number = ansi_code,
-                    "bn" => colors.byte_offset = ansi_code,
-                    "se" => colors.separator = ansi_code,
-                    _ => {},
+                    let Some(ansi_code) = Self::parse_color_spec(spec) else {
+                        continue;
+                    };
+
+                    match capability {
+                        "mt" => {
+                            colors.selected_match = ansi_code.clone();
+                            colors.context_match = ansi_code;
+                        },
+                        "ms" => colors.selected_match = ansi_code,
+                        "mc" => colors.context_match = ansi_code,
+                        "sl" => colors.selected_line = ansi_code,
+                        "cx" => colors.context_line = ansi_code,
+                        "fn" => colors.file_name = ansi_code,
+                        "ln" => colors.line_number = ansi_code,
+                        "bn" => colors.byte_offset = ansi_code,
+                        "se" => colors.separator = ansi_code,
+                        _ => {},
+                    }
                 }
             }
         }
 
         colors
     }
-}
\ No newline at end of file
+
+    /// Returns a message for each entry of a colors string that
+    /// [parse_colors_onto](Colors::parse_colors_onto) ignores.
+    fn validate_colors(colors_str: &str) -> Vec<String> {
+        if !colors_str.is_ascii() {
+            return vec!(String::from("colors contain non-ASCII characters and were ignored"));
+        }
+
+        let mut warnings = Vec::new();
+
+        for option in colors_str.split(':') {
+            match option {
+                "rv" | "ne" | "" => {},
+                _ => match option.split_once('=') {
+                    None => {
+                        warnings.push(format!("unknown boolean color capability \"{}\"", option));
+                    },
+                    Some((capability, ansi_code)) => {
+                        if !Self::is_capability(capability) {
+                            warnings.push(format!("unknown color capability \"{}\"", capability));
+                        } else if Self::parse_color_spec(ansi_code).is_none() {
+                            warnings.push(format!(
+                                "invalid color \"{}\" for color capability \"{}\"",
+                                ansi_code,
+                                capability
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+
+        warnings
+    }
+
+    /// Converts a color value into an SGR parameter string. A value is either a raw
+    /// SGR parameter string like `1;33`, or a comma separated list of:
+    /// *   styles: `bold`, `dim`, `italic`, `underline`, `blink`, `reverse`, `hidden`
+    ///     and `strikethrough`.
+    /// *   colors: `black`, `red`, `green`, `yellow`, `blue`, `magenta`, `cyan` and `white`,
+    ///     optionally prefixed with `bright-`.
+    /// *   256-color palette indices: `color0` to `color255`.
+    /// *   truecolor hex codes: `#ff8800` or `#f80`.
+    /// *   raw SGR parameters.
+    ///
+    /// Colors are used for the foreground unless prefixed with `bg-`, e.g. `bg-blue`.
+    /// Returns `None` if any part of the value is malformed.
+    pub fn parse_color_spec(spec: &str) -> Option<String> {
+        if Self::is_valid_sgr(spec) {
+            return Some(String::from(spec));
+        }
+
+        let mut codes: Vec<String> = Vec::new();
+
+        for token in spec.split(',') {
+            let token = token.trim().to_ascii_lowercase();
+
+            if !token.is_empty() && Self::is_valid_sgr(&token) {
+                codes.push(token);
+                continue;
+            }
+
+            let style = match token.as_str() {
+                "bold" => Some(1),
+                "dim" => Some(2),
+                "italic" => Some(3),
+                "underline" => Some(4),
+                "blink" => Some(5),
+                "reverse" => Some(7),
+                "hidden" => Some(8),
+                "strikethrough" => Some(9),
+                _ => None,
+            };
+
+            if let Some(style) = style {
+                codes.push(style.to_string());
+                continue;
+            }
+
+            let (background, color) = match token.strip_prefix("bg-") {
+                Some(color) => (true, color),
+                None => (false, token.as_str()),
+            };
+
+            codes.push(Self::parse_color(color, background)?);
+        }
+
+        Some(codes.join(";"))
+    }
+
+    /// Converts a single color name, palette index or hex code into SGR parameters.
+    fn parse_color(color: &str, background: bool) -> Option<String> {
+        let (basic, extended) = if background { (40, 48) } else { (30, 38) };
+
+        if let Some(hex) = color.strip_prefix('#') {
+            // channels are sliced by byte offsets
+            if !hex.is_ascii() {
+                return None;
+            }
+
+            let channels: Vec<u8> = match hex.len() {
+                6 => (0..3)
+                    .map(|i| u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).ok())
+                    .collect::<Option<Vec<u8>>>()?,
+                3 => (0..3)
+                    .map(|i| u8::from_str_radix(&hex[i..i + 1], 16).ok().map(|c| c * 17))
+                    .collect::<Option<Vec<u8>>>()?,
+                _ => return None,
+            };
+
+            return Some(format!("{};2;{};{};{}", extended, channels[0], channels[1], channels[2]));
+        }
+
+        if let Some(index) = color.strip_prefix("color") {
+            let index: u8 = index.parse().ok()?;
+            return Some(format!("{};5;{}", extended, index));
+        }
+
+        let (bright, name) = match color.strip_prefix("bright-") {
+            Some(name) => (true, name),
+            None => (false, color),
+        };
+
+        let offset = COLOR_NAMES.iter().position(|known| *known == name)?;
+        let code = if bright { basic + 60 } else { basic } + offset;
+
+        Some(code.to_string())
+    }
+
+    /// Checks if `capability` names a color that can be set.
+    fn is_capability(capability: &str) -> bool {
+        matches!(capability, "mt" | "ms" | "mc" | "sl" | "cx" | "fn" | "ln" | "bn" | "se")
+    }
+
+    /// Checks if `ansi_code` is a valid SGR parameter string, i.e. only
+    /// contains digits and semicolons. An empty string disables the color.
+    fn is_valid_sgr(ansi_code: &str) -> bool {
+        ansi_code.chars().all(|c| c.is_ascii_digit() || c == ';')
+    }
+}
@@ -6,13 +6,15 @@ fn default_colors() {
     let default = Colors::default();
     let expected_default = Colors {
         selected_match: String::from("1;33"),
-        context_match: String::from("1:33"),
+        context_match: String::from("1;33"),
         selected_line: String::from(""),
         context_line: String::from(""),
         file_name: String::from("32"),
         line_number: String::from("31"),
         byte_offset: String::from("36"),
         separator: String::from("35"),
+        reverse: false,
+        no_erase: false,
     };
 
     assert_eq!(default, expected_default);
@@ -22,7 +24,7 @@ fn default_colors() {
 fn parse_valid_colors_string() {
     let colors_string = "ms=30:mc=91:fn=93:ln=95:se=107;91";
 
-    let colors = Colors::parse_colors(colors_string.to_string());
+    let colors = Colors::parse_colors_onto(Colors::default(), colors_string.to_string());
 
     let expected_colors = Colors {
         selected_match: String::from("30"),
@@ -33,13 +35,15 @@ fn parse_valid_colors_string() {
         line_number: String::from("95"),
         byte_offset: String::from("36"),
         separator: String::from("107;91"),
+        reverse: false,
+        no_erase: false,
     };
 
     assert_eq!(colors, expected_colors);
 
     let colors_string = "mt=30";
 
-    let colors = Colors::parse_colors(colors_string.to_string());
+    let colors = Colors::parse_colors_onto(Colors::default(), colors_string.to_string());
 
     let expected_colors = Colors {
         selected_match: String::from("30"),
@@ -50,6 +54,8 @@ fn parse_valid_colors_string() {
         line_number: String::from("31"),
         byte_offset: String::from("36"),
         separator: String::from("35"),
+        reverse: false,
+        no_erase: false,
     };
 
     assert_eq!(colors, expected_colors);
@@ -59,7 +65,7 @@ fn parse_valid_colors_string() {
 fn parse_empty_colors_string() {
     let colors_string = "";
 
-    let colors = Colors::parse_colors(colors_string.to_string());
+    let colors = Colors::parse_colors_onto(Colors::default(), colors_string.to_string());
 
     assert_eq!(colors, Colors::default());
 }
@@ -68,7 +74,127 @@ fn parse_empty_colors_string() {
 fn parse_invalid_ascii_colors_string() {
     let colors_string = "ms=30:\u{C398}";
 
-    let colors = Colors::parse_colors(colors_string.to_string());
+    let colors = Colors::parse_colors_onto(Colors::default(), colors_string.to_string());
 
     assert_eq!(colors, Colors::default());
-}
\ No newline at end of file
+}
+
+#[test]
+fn parse_boolean_capabilities() {
+    let colors = Colors::parse_colors_onto(Colors::default(), String::from("rv:ms=31:ne"));
+
+    let expected_colors = Colors {
+        selected_match: String::from("31"),
+        reverse: true,
+        no_erase: true,
+        ..Default::default()
+    };
+
+    assert_eq!(colors, expected_colors);
+}
+
+#[test]
+fn parse_empty_capability() {
+    let colors = Colors::parse_colors_onto(Colors::default(), String::from("ms=:fn=1;32"));
+
+    let expected_colors = Colors {
+        selected_match: String::new(),
+        file_name: String::from("1;32"),
+        ..Default::default()
+    };
+
+    assert_eq!(colors, expected_colors);
+}
+
+#[test]
+fn parse_malformed_colors_string() {
+    let colors_string = "ms=ref:xx=31:fn32:zz:sl=1";
+
+    let colors = Colors::parse_colors_onto(Colors::default(), colors_string.to_string());
+
+    let expected_colors = Colors {
+        selected_line: String::from("1"),
+        ..Default::default()
+    };
+
+    assert_eq!(colors, expected_colors);
+
+    let warnings = Colors::validate_colors(colors_string);
+
+    assert_eq!(warnings, vec!(
+        String::from("invalid color \"ref\" for color capability \"ms\""),
+        String::from("unknown color capability \"xx\""),
+        String::from("unknown boolean color capability \"fn32\""),
+        String::from("unknown boolean color capability \"zz\""),
+    ));
+}
+
+#[test]
+fn validate_valid_colors_string() {
+    assert!(Colors::validate_colors("ms=01;31:mc=01;31:sl=:cx=:fn=35:ln=32:bn=32:se=36:rv:ne").is_empty());
+    assert_eq!(Colors::validate_colors("ms=\u{C398}").len(), 1);
+}
+
+#[test]
+fn parse_named_color_specs() {
+    assert_eq!(Colors::parse_color_spec("1;33"), Some(String::from("1;33")));
+    assert_eq!(Colors::parse_color_spec(""), Some(String::new()));
+    assert_eq!(Colors::parse_color_spec("green,bold"), Some(String::from("32;1")));
+    assert_eq!(Colors::parse_color_spec("Bright-Red,underline"), Some(String::from("91;4")));
+    assert_eq!(Colors::parse_color_spec("bg-blue,white"), Some(String::from("44;37")));
+    assert_eq!(Colors::parse_color_spec("bg-bright-black"), Some(String::from("100")));
+    assert_eq!(Colors::parse_color_spec("1,italic"), Some(String::from("1;3")));
+}
+
+#[test]
+fn parse_extended_color_specs() {
+    assert_eq!(Colors::parse_color_spec("#ff8800,underline"), Some(String::from("38;2;255;136;0;4")));
+    assert_eq!(Colors::parse_color_spec("bg-#f80"), Some(String::from("48;2;255;136;0")));
+    assert_eq!(Colors::parse_color_spec("color208"), Some(String::from("38;5;208")));
+    assert_eq!(Colors::parse_color_spec("bg-color17,bold"), Some(String::from("48;5;17;1")));
+}
+
+#[test]
+fn parse_invalid_color_specs() {
+    assert_eq!(Colors::parse_color_spec("purple"), None);
+    assert_eq!(Colors::parse_color_spec("color256"), None);
+    assert_eq!(Colors::parse_color_spec("#ff88"), None);
+    assert_eq!(Colors::parse_color_spec("#gg8800"), None);
+    assert_eq!(Colors::parse_color_spec("#€"), None);
+    assert_eq!(Colors::parse_color_spec("#€€"), None);
+    assert_eq!(Colors::parse_color_spec("bg-#ab€"), None);
+    assert_eq!(Colors::parse_color_spec("bg-bold"), None);
+    assert_eq!(Colors::parse_color_spec("green,"), None);
+}
+
+#[test]
+fn parse_colors_string_with_names() {
+    let colors = Colors::parse_colors_onto(Colors::default(), String::from("fn=green,bold:ms=#ff8800,underline:se=90"));
+
+    let expected_colors = Colors {
+        file_name: String::from("32;1"),
+        selected_match: String::from("38;2;255;136;0;4"),
+        separator: String::from("90"),
+        ..Default::default()
+    };
+
+    assert_eq!(colors, expected_colors);
+}
+
+#[test]
+fn built_in_themes() {
+    for name in THEMES {
+        assert!(Colors::theme(name).is_some());
+    }
+
+    assert_eq!(Colors::theme("default"), Some(Colors::default()));
+    assert_eq!(Colors::theme("solarized"), None);
+
+    let colorblind = Colors::theme("colorblind").unwrap();
+    assert_eq!(colorblind.selected_match, colorblind.context_match);
+    assert_eq!(colorblind.selected_line, String::new());
+
+    let themed = Colors::parse_colors_onto(Colors::theme("light").unwrap(), String::from("fn=32"));
+    assert_eq!(themed.file_name, String::from("32"));
+    assert_eq!(themed.selected_match, String::from("1;31"));
+}
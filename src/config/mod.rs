@@ -32,6 +32,22 @@ impl Config {
             options.parse_option(arg)?;
         }
 
+        if (options.in_place.is_some() || options.diff) && options.replace.is_none() {
+            return Err(Box::from("--in-place and --diff require a --replace template!"));
+        }
+
+        if options.diff && options.record_separator.is_some() {
+            return Err(Box::from("--diff can not be used with record separators!"));
+        }
+
+        if options.rules.is_some() && options.invert_match {
+            return Err(Box::from("--rules can not be used with --invert-match!"));
+        }
+
+        if options.show_pattern.is_some() && options.invert_match {
+            return Err(Box::from("--show-pattern can not be used with --invert-match!"));
+        }
+
         if source_args.is_empty() {
             source_args.push(String::from("-"));
         }
@@ -86,6 +102,11 @@ impl Config {
                 }
             }
             else if arg.len() > 1 && arg.starts_with("-") {
+                // rules replace patterns in lint mode
+                if arg.starts_with("--rules=") {
+                    explicit_pattern = true;
+                }
+
                 // checks length to avoid detecting
                 // stdin path "-" as an option
                 options.push(arg);
@@ -81,7 +81,7 @@ fn multiple_source_file_prefix() {
     );
     let config = Config::new(args).unwrap();
     assert_eq!(config.sources.len(), 2);
-    assert_eq!(config.options.file_prefix, true);
+    assert!(config.options.file_prefix);
 
     // disable file prefix
     let args = vec!(
@@ -93,5 +93,52 @@ fn multiple_source_file_prefix() {
     );
     let config = Config::new(args).unwrap();
     assert_eq!(config.sources.len(), 2);
-    assert_eq!(config.options.file_prefix, false);
+    assert!(!config.options.file_prefix);
+}
+
+#[test]
+fn editing_requires_replace() {
+    let args = vec!(String::from("./target"), String::from("dew"), String::from("--diff"));
+    assert!(Config::new(args).is_err());
+
+    let args = vec!(String::from("./target"), String::from("dew"), String::from("--in-place"));
+    assert!(Config::new(args).is_err());
+
+    let args = vec!(
+        String::from("./target"),
+        String::from("dew"),
+        String::from("--in-place=.bak"),
+        String::from("--replace=mist"),
+    );
+    assert!(Config::new(args).is_ok());
+
+    let args = vec!(
+        String::from("./target"),
+        String::from("dew"),
+        String::from("--diff"),
+        String::from("--paragraph"),
+        String::from("--replace=mist"),
+    );
+    assert!(Config::new(args).is_err());
+}
+
+#[test]
+fn rules_replace_patterns() {
+    let args = vec!(String::from("./target"), String::from("--rules=res/test/lint.rules"), String::from("res/test/haiku.txt"));
+    let config = Config::new(args).unwrap();
+    assert_eq!(config.sources.len(), 1);
+    assert_eq!(config.sources[0].path, "res/test/haiku.txt");
+    assert!(config.options.patterns.is_empty());
+
+    let args = vec!(String::from("./target"), String::from("--rules=res/test/lint.rules"), String::from("-v"));
+    assert!(Config::new(args).is_err());
+}
+
+#[test]
+fn show_pattern_requires_matches() {
+    let args = vec!(String::from("./target"), String::from("dew"), String::from("--show-pattern"));
+    assert!(Config::new(args).is_ok());
+
+    let args = vec!(String::from("./target"), String::from("dew"), String::from("--show-pattern"), String::from("-v"));
+    assert!(Config::new(args).is_err());
 }
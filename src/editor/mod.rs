@@ -0,0 +1,302 @@
+mod test;
+
+use crate::matcher;
+use crate::options;
+use crate::output;
+use crate::sink;
+use crate::source;
+
+use matcher::Record;
+use options::Options;
+use output::BufferedWriter;
+use sink::{Sink, SinkLine, SourceStats};
+use source::Source;
+use std::fs;
+use std::io::{stderr, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+
+/// Number of unchanged lines shown around changes in a unified diff.
+const DIFF_CONTEXT: usize = 3;
+
+/// Applies replacements to the selected lines of sources.
+///
+/// Sources are either rewritten in place, see [Options::in_place], or, when
+/// [Options::diff] is set, left untouched while a unified diff of the changes
+/// that would be made is written instead.
+pub struct Editor {
+    /// Buffers diff output and writes it to stdout.
+    writer: BufferedWriter,
+    /// Edits collected for the source currently being searched.
+    edits: Vec<Edit>,
+}
+
+/// Replacement of one or more consecutive lines of a source. Lines are only
+/// replaced together when a multiline match spans them.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Edit {
+    /// Index of the first line inside of the source, starting from 0.
+    line_index: usize,
+    /// Number of lines that are replaced.
+    line_count: usize,
+    /// Byte offset of the start of the first line inside of the source.
+    byte_offset: usize,
+    /// Length of the original lines in bytes, including the terminators
+    /// between them but not the terminator of the last line.
+    length: usize,
+    /// Content of the lines after replacements have been applied.
+    replaced: String,
+}
+
+impl Edit {
+    /// Returns the index of the last line that is replaced.
+    fn last_line_index(&self) -> usize {
+        self.line_index + self.line_count - 1
+    }
+}
+
+impl Default for Editor {
+    /// Creates new instance of Editor with default
+    /// buffer size and stdout as destination.
+    fn default() -> Editor {
+        Editor {
+            writer: BufferedWriter::default(),
+            edits: Vec::new(),
+        }
+    }
+}
+
+impl Sink for Editor {
+    fn begin_source(&mut self, _options: &Options, _source: &Source) {
+        self.edits.clear();
+    }
+
+    /// Records the replaced content of lines that contain matches. Lines
+    /// continuing a multiline match are joined with the edit of the line the
+    /// match started on, so the terminators between them are replaced as well.
+    fn matched_line(&mut self, options: &Options, _source: &Source, line: &SinkLine) {
+        let line_index = line.line_number - 1;
+
+        // without inverting only lines inside of a multiline match have no matches
+        let inside_match = line.matches.is_empty() && options.multiline && !options.invert_match;
+
+        if line.continues_match || inside_match {
+            if let Some(edit) = self.edits.last_mut().filter(|edit| edit.last_line_index() + 1 == line_index) {
+                edit.line_count += 1;
+                edit.length = line.byte_offset + line.text.len() - edit.byte_offset;
+                edit.replaced.push_str(&line.replaced_text());
+                return;
+            }
+        }
+
+        if line.replacements.is_empty() {
+            return;
+        }
+
+        self.edits.push(Edit {
+            line_index,
+            line_count: 1,
+            byte_offset: line.byte_offset,
+            length: line.text.len(),
+            replaced: line.replaced_text(),
+        });
+    }
+
+    fn context_line(&mut self, _options: &Options, _source: &Source, _line: &SinkLine) {}
+
+    /// Writes a diff or rewrites the source once all of its edits are known.
+    /// Edits that leave their lines unchanged are dropped.
+    fn end_source(&mut self, options: &Options, source: &Source, _stats: &SourceStats) {
+        self.edits.retain(|edit| edit.replaced != source.data[edit.byte_offset..edit.byte_offset + edit.length]);
+
+        if self.edits.is_empty() {
+            return;
+        }
+
+        let edited_data = apply_edits(&source.data, &self.edits);
+
+        if options.diff {
+            let records = matcher::split_records(&source.data, options);
+            let diff = unified_diff(&source.path, &records, &self.edits);
+
+            self.writer.push_str(&diff);
+        }
+        else if let Some(suffix) = &options.in_place {
+            if let Err(msg) = write_in_place(&source.path, &edited_data, suffix) {
+                if !options.no_messages {
+                    writeln!(
+                        stderr(),
+                        "grepRS: could not edit {}: {}",
+                        source.path,
+                        msg
+                    ).expect("grepRS: could not write to stderr!");
+                }
+            }
+        }
+    }
+
+    fn finish(&mut self, options: &Options) {
+        if !options.silent {
+            self.writer.write_and_flush();
+        }
+    }
+}
+
+/// Returns `data` with every edit applied. Edits must be ordered by offset.
+fn apply_edits(data: &str, edits: &[Edit]) -> String {
+    let mut edited_data = String::with_capacity(data.len());
+
+    let mut previous = 0;
+    for edit in edits {
+        edited_data.push_str(&data[previous..edit.byte_offset]);
+        edited_data.push_str(&edit.replaced);
+        previous = edit.byte_offset + edit.length;
+    }
+
+    edited_data.push_str(&data[previous..]);
+
+    edited_data
+}
+
+/// Replaces the file at `path` with `data`. The data is written to a temporary file
+/// next to the original which is then renamed over it, preserving the original's
+/// permissions. A copy of the original is kept at `path` + `suffix` when `suffix`
+/// is not empty.
+fn write_in_place(path: &str, data: &str, suffix: &str) -> Result<(), Box<dyn std::error::Error>> {
+    if path == "-" {
+        return Err(Box::from("standard input can not be edited in place"));
+    }
+
+    let original = Path::new(path);
+    let permissions = fs::metadata(original)?.permissions();
+    let temp_path = temp_path_for(original);
+
+    // the backup is made first, so nothing is left to clean up when it fails
+    if !suffix.is_empty() {
+        fs::copy(original, String::from(path) + suffix)?;
+    }
+
+    let write_result = fs::write(&temp_path, data)
+        .and_then(|_| fs::set_permissions(&temp_path, permissions));
+
+    if let Err(io_err) = write_result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(Box::new(io_err));
+    }
+
+    if let Err(io_err) = fs::rename(&temp_path, original) {
+        let _ = fs::remove_file(&temp_path);
+        return Err(Box::new(io_err));
+    }
+
+    Ok(())
+}
+
+/// Builds a path for a temporary file in the same directory as `path`,
+/// so that renaming it over `path` is atomic.
+fn temp_path_for(path: &Path) -> PathBuf {
+    let file_name = path.file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    path.with_file_name(format!(".{}.greprs-{}.tmp", file_name, process::id()))
+}
+
+/// Builds a unified diff between the lines of a source and the lines with `edits`
+/// applied. Lines keep their original terminators. Edits must be ordered by line.
+fn unified_diff(path: &str, lines: &[Record], edits: &[Edit]) -> String {
+    let last_line = lines.len() - 1;
+
+    let mut diff = format!("--- {}\n+++ {}\n", path, path);
+
+    // groups edits into hunks whose context lines overlap
+    let mut hunks: Vec<&[Edit]> = Vec::new();
+    let mut hunk_start = 0;
+    for index in 1..=edits.len() {
+        let is_hunk_end = index == edits.len() ||
+            edits[index].line_index - edits[index - 1].last_line_index() > 2 * DIFF_CONTEXT;
+
+        if is_hunk_end {
+            hunks.push(&edits[hunk_start..index]);
+            hunk_start = index;
+        }
+    }
+
+    // difference between new and old line numbers caused by previous hunks
+    let mut line_delta: isize = 0;
+
+    for hunk in hunks {
+        let first = hunk[0].line_index.saturating_sub(DIFF_CONTEXT);
+        let last = usize::min(hunk[hunk.len() - 1].last_line_index() + DIFF_CONTEXT, last_line);
+
+        let mut hunk_body = String::new();
+        // added lines are held back until the end of a run of changed lines
+        let mut additions = String::new();
+        let mut new_count = 0;
+        // remaining lines of an edit replacing several lines
+        let mut removed_lines = 0;
+        let mut edits = hunk.iter().peekable();
+
+        for (index, line) in lines.iter().enumerate().take(last + 1).skip(first) {
+            if removed_lines > 0 {
+                push_diff_line(&mut hunk_body, '-', line.text, line.terminator);
+                removed_lines -= 1;
+                continue;
+            }
+
+            match edits.next_if(|edit| edit.line_index == index) {
+                Some(edit) => {
+                    push_diff_line(&mut hunk_body, '-', line.text, line.terminator);
+                    removed_lines = edit.line_count - 1;
+
+                    // the replacement ends with the terminator of the last replaced line
+                    let terminator = lines[edit.last_line_index()].terminator;
+                    let new_lines: Vec<&str> = edit.replaced.split('\n').collect();
+                    for new_line in &new_lines {
+                        push_diff_line(&mut additions, '+', new_line, terminator);
+                    }
+
+                    new_count += new_lines.len();
+                },
+                None => {
+                    hunk_body.push_str(&additions);
+                    additions.clear();
+
+                    push_diff_line(&mut hunk_body, ' ', line.text, line.terminator);
+                    new_count += 1;
+                }
+            }
+        }
+
+        hunk_body.push_str(&additions);
+
+        let old_count = last + 1 - first;
+        let new_start = (first + 1) as isize + line_delta;
+
+        diff.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            first + 1,
+            old_count,
+            new_start,
+            new_count
+        ));
+        diff.push_str(&hunk_body);
+
+        line_delta += new_count as isize - old_count as isize;
+    }
+
+    diff
+}
+
+/// Appends a single line to the body of a diff hunk. A line without a
+/// terminator is marked as having no newline at the end of the file.
+fn push_diff_line(hunk_body: &mut String, prefix: char, line: &str, terminator: &str) {
+    hunk_body.push(prefix);
+    hunk_body.push_str(line);
+
+    if terminator.is_empty() {
+        hunk_body.push_str("\n\\ No newline at end of file\n");
+    } else {
+        hunk_body.push_str(terminator);
+    }
+}
@@ -0,0 +1,175 @@
+#![cfg(test)]
+#![allow(unused_imports)]
+use regex::Regex;
+
+use crate::matcher;
+
+use super::*;
+
+/// Splits `data` into lines with default options.
+fn split_records(data: &str) -> Vec<Record<'_>> {
+    matcher::split_records(data, &Options::default())
+}
+
+/// Searches `data` for `pattern` with replacements and returns the collected edits.
+fn collect_edits(data: &str, pattern: &str, template: &str) -> Vec<Edit> {
+    let options = Options {
+        patterns: vec!(String::from(pattern)),
+        replace: Some(String::from(template)),
+        ..Default::default()
+    };
+
+    collect_edits_with_options(data, &options)
+}
+
+/// Searches `data` with `options` and returns the collected edits.
+fn collect_edits_with_options(data: &str, options: &Options) -> Vec<Edit> {
+    let source = Source {
+        path: String::from("-"),
+        data: String::from(data),
+        bytes: None,
+    };
+
+    let regex: Regex = matcher::build_regex(options).unwrap();
+
+    let mut editor = Editor {
+        writer: BufferedWriter::for_test(),
+        ..Default::default()
+    };
+
+    matcher::search_source(&regex, options, &source, &mut editor);
+
+    editor.edits
+}
+
+#[test]
+fn apply_edits_to_data() {
+    let data = "This world of dew,\nis a world of dew,\nand yet, and yet.";
+    let edits = collect_edits(data, "dew", "mist");
+
+    assert_eq!(edits.len(), 2);
+    assert_eq!(
+        apply_edits(data, &edits),
+        "This world of mist,\nis a world of mist,\nand yet, and yet."
+    );
+}
+
+#[test]
+fn unchanged_lines_are_not_edits() {
+    let edits = collect_edits("dew\nmist\n", "mist", "mist");
+    assert!(edits.is_empty());
+}
+
+#[test]
+fn unified_diff_of_edits() {
+    let data = "1\n2\ndew\n4\n5\n6\n7\n8\n9\n10\n11\ndew\n13";
+    let edits = collect_edits(data, "dew", "mist\nfog");
+
+    let expected_diff = String::from(concat!(
+        "--- -\n+++ -\n",
+        "@@ -1,6 +1,7 @@\n 1\n 2\n-dew\n+mist\n+fog\n 4\n 5\n 6\n",
+        "@@ -9,5 +10,6 @@\n 9\n 10\n 11\n-dew\n+mist\n+fog\n 13\n",
+        "\\ No newline at end of file\n",
+    ));
+
+    assert_eq!(unified_diff("-", &split_records(data), &edits), expected_diff);
+}
+
+#[test]
+fn unified_diff_groups_consecutive_changes() {
+    let data = "dew\ndew\nyet\n";
+    let edits = collect_edits(data, "dew", "mist");
+
+    let expected_diff = String::from(concat!(
+        "--- -\n+++ -\n",
+        "@@ -1,3 +1,3 @@\n-dew\n-dew\n+mist\n+mist\n yet\n",
+    ));
+
+    assert_eq!(unified_diff("-", &split_records(data), &edits), expected_diff);
+}
+
+#[test]
+fn unified_diff_without_trailing_newline() {
+    let data = "a\ndew";
+    let edits = collect_edits(data, "dew", "mist");
+
+    let expected_diff = String::from(concat!(
+        "--- -\n+++ -\n",
+        "@@ -1,2 +1,2 @@\n a\n-dew\n\\ No newline at end of file\n+mist\n\\ No newline at end of file\n",
+    ));
+
+    assert_eq!(unified_diff("-", &split_records(data), &edits), expected_diff);
+}
+
+#[test]
+fn write_source_in_place() {
+    let dir = std::env::temp_dir().join(format!("greprs-editor-test-{}", process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("haiku.txt");
+    let path_str = path.to_str().unwrap();
+
+    fs::write(&path, "This world of dew,\n").unwrap();
+
+    write_in_place(path_str, "This world of mist,\n", ".bak").unwrap();
+
+    assert_eq!(fs::read_to_string(&path).unwrap(), "This world of mist,\n");
+    assert_eq!(fs::read_to_string(String::from(path_str) + ".bak").unwrap(), "This world of dew,\n");
+    assert!(!temp_path_for(&path).exists());
+
+    // the backup can't be created inside of a file
+    assert!(write_in_place(path_str, "mist\n", "/backup").is_err());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "This world of mist,\n");
+    assert!(!temp_path_for(&path).exists());
+
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert!(write_in_place("-", "", "").is_err());
+}
+
+#[test]
+fn unified_diff_keeps_crlf_line_endings() {
+    let data = "dew\r\nyet\r\n";
+    let edits = collect_edits(data, "dew", "mist");
+
+    let expected_diff = String::from(concat!(
+        "--- -\n+++ -\n",
+        "@@ -1,2 +1,2 @@\n-dew\r\n+mist\r\n yet\r\n",
+    ));
+
+    assert_eq!(unified_diff("-", &split_records(data), &edits), expected_diff);
+    assert_eq!(apply_edits(data, &edits), "mist\r\nyet\r\n");
+}
+
+#[test]
+fn multiline_matches_replace_terminators() {
+    let mut options = Options {
+        multiline: true,
+        patterns: vec!(String::from(r"a\nb")),
+        replace: Some(String::from("X")),
+        ..Default::default()
+    };
+
+    let data = "a\nb\nc\n";
+    let edits = collect_edits_with_options(data, &options);
+
+    assert_eq!(edits, vec!(Edit { line_index: 0, line_count: 2, byte_offset: 0, length: 3, replaced: String::from("X") }));
+    assert_eq!(apply_edits(data, &edits), "X\nc\n");
+
+    let expected_diff = String::from(concat!(
+        "--- -\n+++ -\n",
+        "@@ -1,3 +1,2 @@\n-a\n-b\n+X\n c\n",
+    ));
+
+    assert_eq!(unified_diff("-", &split_records(data), &edits), expected_diff);
+
+    // lines inside of a match and matches left unchanged on their first line
+    options.patterns = vec!(String::from(r"a\n\nb"));
+    options.replace = Some(String::from("a"));
+
+    let data = "a\n\nb\nc";
+    let edits = collect_edits_with_options(data, &options);
+
+    assert_eq!(edits.len(), 1);
+    assert_eq!(edits[0].line_count, 3);
+    assert_eq!(apply_edits(data, &edits), "a\nc");
+}
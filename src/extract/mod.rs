@@ -0,0 +1,155 @@
+mod test;
+
+use crate::matcher;
+use crate::options;
+use crate::output;
+use crate::sink;
+use crate::source;
+
+use options::{ExtractColumn, ExtractFormat, Options};
+use output::BufferedWriter;
+use regex::Regex;
+use sink::{Sink, SinkLine, SourceStats};
+use source::Source;
+use std::error::Error;
+
+
+/// Writes the named capture groups of every match as a row of a CSV or TSV table.
+///
+/// The first row is a header with the names of the groups, preceded by the names of
+/// any [extra columns](Options::extract_columns). Groups that didn't participate in a
+/// match are empty cells. CSV cells are quoted when needed, TSV cells escape tabs,
+/// line breaks and backslashes.
+pub struct Extractor {
+    /// Buffers rows and writes them to stdout.
+    writer: BufferedWriter,
+    /// Format of the table.
+    format: ExtractFormat,
+    /// Regex of the search, used for capture groups.
+    regex: Regex,
+    /// Names of the capture groups written as columns.
+    names: Vec<String>,
+}
+
+impl Extractor {
+    /// Creates new instance of Extractor with stdout as destination. Fails
+    /// if `regex` has no named capture groups.
+    pub fn new(options: &Options, format: ExtractFormat, regex: Regex) -> Result<Extractor, Box<dyn Error>> {
+        let mut extractor = Extractor {
+            writer: BufferedWriter::default(),
+            format,
+            names: Self::group_names(&regex)?,
+            regex,
+        };
+
+        extractor.append_header(options);
+
+        Ok(extractor)
+    }
+
+    /// Returns the names of the capture groups of `regex` in order.
+    fn group_names(regex: &Regex) -> Result<Vec<String>, Box<dyn Error>> {
+        let names: Vec<String> = regex.capture_names().flatten().map(String::from).collect();
+
+        if names.is_empty() {
+            return Err(Box::from("--extract requires a pattern with named capture groups, e.g. (?P<name>...)!"));
+        }
+
+        Ok(names)
+    }
+
+    /// Writes the header row.
+    fn append_header(&mut self, options: &Options) {
+        let mut cells: Vec<String> = options.extract_columns.iter()
+            .map(|column| match column {
+                ExtractColumn::Path => String::from("path"),
+                ExtractColumn::Line => String::from("line"),
+            })
+            .collect();
+
+        cells.extend(self.names.iter().cloned());
+
+        self.append_row(options, &cells);
+    }
+
+    /// Writes a row of cells unless silent.
+    fn append_row(&mut self, options: &Options, cells: &[String]) {
+        if options.silent {
+            return;
+        }
+
+        let (separator, escape): (&str, fn(&str) -> String) = match self.format {
+            ExtractFormat::Csv => (",", quote_csv),
+            ExtractFormat::Tsv => ("\t", escape_tsv),
+        };
+
+        let row: Vec<String> = cells.iter().map(|cell| escape(cell)).collect();
+
+        self.writer.push_str(&row.join(separator));
+        self.writer.push('\n');
+    }
+}
+
+impl Sink for Extractor {
+    fn begin_source(&mut self, _options: &Options, _source: &Source) {}
+
+    /// Writes a row for every match of the line.
+    fn matched_line(&mut self, options: &Options, source: &Source, line: &SinkLine) {
+        for span in &line.matches {
+            let Some(captures) = matcher::match_captures(&self.regex, options, source, line, span) else {
+                continue;
+            };
+
+            let mut cells: Vec<String> = options.extract_columns.iter()
+                .map(|column| match column {
+                    ExtractColumn::Path if source.path == "-" => String::from("(standard input)"),
+                    ExtractColumn::Path => source.path.clone(),
+                    ExtractColumn::Line => line.line_number.to_string(),
+                })
+                .collect();
+
+            for name in &self.names {
+                cells.push(captures.name(name).map_or(String::new(), |group| String::from(group.as_str())));
+            }
+
+            self.append_row(options, &cells);
+        }
+    }
+
+    fn context_line(&mut self, _options: &Options, _source: &Source, _line: &SinkLine) {}
+
+    fn end_source(&mut self, _options: &Options, _source: &Source, _stats: &SourceStats) {}
+
+    fn finish(&mut self, options: &Options) {
+        if !options.silent {
+            self.writer.write_and_flush();
+        }
+    }
+}
+
+/// Quotes a CSV cell if it contains a separator, quote or line break.
+/// Quotes inside of quoted cells are doubled.
+fn quote_csv(cell: &str) -> String {
+    if cell.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        String::from(cell)
+    }
+}
+
+/// Escapes tabs, line breaks and backslashes inside of a TSV cell.
+fn escape_tsv(cell: &str) -> String {
+    let mut escaped = String::with_capacity(cell.len());
+
+    for c in cell.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '\t' => escaped.push_str("\\t"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
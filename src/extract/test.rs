@@ -0,0 +1,71 @@
+#![allow(unused_imports)]
+use crate::matcher;
+
+use super::*;
+
+#[test]
+fn quote_csv_cells() {
+    assert_eq!(quote_csv("plain"), "plain");
+    assert_eq!(quote_csv(""), "");
+    assert_eq!(quote_csv("a,b"), "\"a,b\"");
+    assert_eq!(quote_csv("say \"hi\""), "\"say \"\"hi\"\"\"");
+    assert_eq!(quote_csv("two\nlines"), "\"two\nlines\"");
+}
+
+#[test]
+fn escape_tsv_cells() {
+    assert_eq!(escape_tsv("plain, text"), "plain, text");
+    assert_eq!(escape_tsv("a\tb\\c\nd"), "a\\tb\\\\c\\nd");
+}
+
+#[test]
+fn requires_named_groups() {
+    let regex = Regex::new("(\\w+)=(\\d+)").unwrap();
+    assert!(Extractor::group_names(&regex).is_err());
+
+    let regex = Regex::new("(?P<key>\\w+)=(\\d+)(?P<unit>[a-z]+)?").unwrap();
+    assert_eq!(Extractor::group_names(&regex).unwrap(), vec!(String::from("key"), String::from("unit")));
+}
+
+#[test]
+fn extracts_rows_of_named_groups() {
+    let source = Source {
+        path: String::from("log.txt"),
+        data: String::from("level=warn msg=\"disk, full\"\nlevel=info\nnothing here\n"),
+        bytes: None,
+    };
+
+    let mut options = Options {
+        patterns: vec!(String::from("level=(?P<level>\\w+)(?: msg=(?P<msg>\".*\"))?")),
+        extract_columns: vec!(ExtractColumn::Path, ExtractColumn::Line),
+        ..Default::default()
+    };
+
+    let regex = matcher::build_regex(&options).unwrap();
+
+    let mut extractor = Extractor {
+        writer: BufferedWriter::for_test(),
+        format: ExtractFormat::Csv,
+        names: Extractor::group_names(&regex).unwrap(),
+        regex: regex.clone(),
+    };
+    extractor.append_header(&options);
+
+    matcher::search_source(&regex, &options, &source, &mut extractor);
+
+    let expected_buffer = concat!(
+        "path,line,level,msg\n",
+        "log.txt,1,warn,\"\"\"disk, full\"\"\"\n",
+        "log.txt,2,info,\n",
+    );
+
+    assert_eq!(extractor.writer.as_str(), expected_buffer);
+
+    options.extract_columns = Vec::new();
+    extractor.format = ExtractFormat::Tsv;
+    extractor.writer.clear();
+    extractor.append_header(&options);
+    matcher::search_source(&regex, &options, &source, &mut extractor);
+
+    assert_eq!(extractor.writer.as_str(), "level\tmsg\nwarn\t\"disk, full\"\ninfo\t\n");
+}
@@ -0,0 +1,111 @@
+mod test;
+
+use std::env::var_os;
+use std::error::Error;
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Starts an OSC 8 hyperlink, followed by the link target.
+const OSC8_START: &str = "\x1b]8;;";
+/// Terminates the link target or the linked text of an OSC 8 hyperlink.
+const OSC8_END: &str = "\x1b\\";
+/// Placeholders that can be used in a hyperlink format.
+const PLACEHOLDERS: [&str; 4] = ["{host}", "{path}", "{line}", "{column}"];
+
+/// Built-in hyperlink formats that can be selected by name.
+pub const PRESETS: [(&str, &str); 9] = [
+    ("default", "file://{host}{path}"),
+    ("file", "file://{host}{path}"),
+    ("kitty", "file://{host}{path}#{line}"),
+    ("vscode", "vscode://file{path}:{line}:{column}"),
+    ("vscode-insiders", "vscode-insiders://file{path}:{line}:{column}"),
+    ("vscodium", "vscodium://file{path}:{line}:{column}"),
+    ("cursor", "cursor://file{path}:{line}:{column}"),
+    ("macvim", "mvim://open?url=file://{path}&line={line}&column={column}"),
+    ("textmate", "txmt://open?url=file://{path}&line={line}&column={column}"),
+];
+
+/// Resolves the value of `--hyperlink-format` into a format string. The value is
+/// either the name of one of the [PRESETS], `none` to disable hyperlinks, or a
+/// format containing a `{path}` placeholder, e.g. `file://{host}{path}#{line}`.
+pub fn parse_format(value: &str) -> Result<Option<String>, Box<dyn Error>> {
+    if value.is_empty() || value == "none" {
+        return Ok(None);
+    }
+
+    if let Some((_, format)) = PRESETS.iter().find(|(name, _)| *name == value) {
+        return Ok(Some(String::from(*format)));
+    }
+
+    if !value.contains("{path}") {
+        return Err(Box::from(format!("Hyperlink format must contain {{path}}: {}", value)));
+    }
+
+    let mut rest = value;
+    while let Some(start) = rest.find('{') {
+        let placeholder = rest[start..].find('}')
+            .map(|end| &rest[start..start + end + 1])
+            .filter(|placeholder| PLACEHOLDERS.contains(placeholder))
+            .ok_or_else(|| format!("Unknown placeholder in hyperlink format: {}", value))?;
+
+        rest = &rest[start + placeholder.len()..];
+    }
+
+    Ok(Some(String::from(value)))
+}
+
+/// Resolves the path of a source into the absolute, percent encoded form used
+/// by [build_url]. Returns `None` for stdin and for paths that can't be resolved.
+pub fn resolve_path(path: &str) -> Option<String> {
+    if path == "-" {
+        return None;
+    }
+
+    let absolute_path = fs::canonicalize(Path::new(path)).ok()?;
+    Some(encode_path(absolute_path.to_str()?))
+}
+
+/// Builds the link target for a line by filling in the placeholders of `format`.
+/// `path` is a source path resolved by [resolve_path].
+pub fn build_url(format: &str, path: &str, line: usize, column: usize) -> String {
+    format
+        .replace("{host}", hostname())
+        .replace("{path}", path)
+        .replace("{line}", &line.to_string())
+        .replace("{column}", &column.to_string())
+}
+
+/// Wraps `text` in an OSC 8 hyperlink pointing to `url`.
+pub fn wrap(text: &str, url: &str) -> String {
+    format!("{}{}{}{}{}{}", OSC8_START, url, OSC8_END, text, OSC8_START, OSC8_END)
+}
+
+/// Percent encodes bytes of `path` that aren't allowed in a URL path.
+pub fn encode_path(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"/-._~!$&'()*+,;=:@".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+
+    encoded
+}
+
+/// Gets the name of the current host, or an empty string if it can't be found.
+/// The name is only looked up once.
+fn hostname() -> &'static str {
+    static HOSTNAME: OnceLock<String> = OnceLock::new();
+
+    HOSTNAME.get_or_init(|| {
+        fs::read_to_string("/proc/sys/kernel/hostname")
+            .ok()
+            .or_else(|| var_os("HOSTNAME").and_then(|host| host.into_string().ok()))
+            .map(|host| String::from(host.trim()))
+            .unwrap_or_default()
+    })
+}
@@ -0,0 +1,60 @@
+#![allow(unused_imports)]
+use super::*;
+
+#[test]
+fn parse_presets() {
+    assert_eq!(parse_format("none").unwrap(), None);
+    assert_eq!(parse_format("").unwrap(), None);
+    assert_eq!(parse_format("default").unwrap(), Some(String::from("file://{host}{path}")));
+    assert_eq!(
+        parse_format("vscode").unwrap(),
+        Some(String::from("vscode://file{path}:{line}:{column}"))
+    );
+}
+
+#[test]
+fn parse_custom_formats() {
+    assert_eq!(
+        parse_format("file://{host}{path}#{line}").unwrap(),
+        Some(String::from("file://{host}{path}#{line}"))
+    );
+
+    assert!(parse_format("file://{host}").is_err());
+    assert!(parse_format("file://{path}#{row}").is_err());
+    assert!(parse_format("file://{path}#{line").is_err());
+}
+
+#[test]
+fn resolve_paths() {
+    let path = fs::canonicalize("Cargo.toml").unwrap();
+
+    assert_eq!(resolve_path("Cargo.toml"), Some(encode_path(path.to_str().unwrap())));
+    assert_eq!(resolve_path("-"), None);
+    assert_eq!(resolve_path("does/not/exist"), None);
+}
+
+#[test]
+fn build_urls() {
+    assert_eq!(
+        build_url("vscode://file{path}:{line}:{column}", "/src/main.rs", 3, 7),
+        "vscode://file/src/main.rs:3:7"
+    );
+    assert_eq!(
+        build_url("file://{host}{path}", "/src/main.rs", 1, 1),
+        format!("file://{}/src/main.rs", hostname())
+    );
+}
+
+#[test]
+fn encode_paths() {
+    assert_eq!(encode_path("/home/user/a file%.txt"), "/home/user/a%20file%25.txt");
+    assert_eq!(encode_path("/tmp/ü"), "/tmp/%C3%BC");
+}
+
+#[test]
+fn wrap_text() {
+    assert_eq!(
+        wrap("path", "file:///path"),
+        "\x1b]8;;file:///path\x1b\\path\x1b]8;;\x1b\\"
+    );
+}
@@ -0,0 +1,332 @@
+mod test;
+
+use crate::options;
+use crate::output;
+use crate::sink;
+use crate::source;
+
+use options::{Options, PatternLabel};
+use output::BufferedWriter;
+use sink::{SearchStats, Sink, SinkLine, SourceStats};
+use source::Source;
+use std::time::Instant;
+
+const BASE64_ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Writes search results as JSON Lines, one JSON object per event.
+///
+/// Every object has a `type` of `begin`, `match`, `context`, `end` or `summary`
+/// and a `data` object describing the event. Text is written as `{"text": ...}`
+/// when it is valid UTF-8, otherwise as base64 encoded `{"bytes": ...}`. Offsets
+/// of sources that aren't valid UTF-8 count their bytes, see [Source::read_data_lossy].
+pub struct JsonPrinter {
+    /// Buffers events and writes them to stdout.
+    writer: BufferedWriter,
+    /// When the search was started, used for the elapsed time in the summary.
+    start: Instant,
+    /// Number of sources searched.
+    searches: usize,
+    /// Number of sources with at least one selected line.
+    searches_with_match: usize,
+    /// Statistics summed over every source.
+    totals: SourceStats,
+    /// Statistics of the entire run, added to the summary when `stats` is set.
+    search_stats: Option<SearchStats>,
+    /// Offsets of the current source, see [offset_map]. Empty for UTF-8 sources.
+    offsets: Vec<(usize, usize)>,
+}
+
+impl Default for JsonPrinter {
+    /// Creates new instance of JsonPrinter with default
+    /// buffer size and stdout as destination.
+    fn default() -> JsonPrinter {
+        JsonPrinter {
+            writer: BufferedWriter::default(),
+            start: Instant::now(),
+            searches: 0,
+            searches_with_match: 0,
+            totals: SourceStats::default(),
+            search_stats: None,
+            offsets: Vec::new(),
+        }
+    }
+}
+
+impl Sink for JsonPrinter {
+    fn begin_source(&mut self, options: &Options, source: &Source) {
+        self.offsets = source.bytes.as_deref().map(offset_map).unwrap_or_default();
+
+        let event = format!(
+            r#"{{"type":"begin","data":{{"path":{}}}}}"#,
+            json_data(display_path(&source.path).as_bytes())
+        );
+
+        self.append_event(options, &event);
+    }
+
+    fn matched_line(&mut self, options: &Options, source: &Source, line: &SinkLine) {
+        let event = self.line_event(options, "match", source, line);
+        self.append_event(options, &event);
+    }
+
+    fn context_line(&mut self, options: &Options, source: &Source, line: &SinkLine) {
+        let event = self.line_event(options, "context", source, line);
+        self.append_event(options, &event);
+    }
+
+    fn end_source(&mut self, options: &Options, source: &Source, stats: &SourceStats) {
+        self.searches += 1;
+        if stats.matched_lines > 0 {
+            self.searches_with_match += 1;
+        }
+
+        self.totals.matched_lines += stats.matched_lines;
+        self.totals.matches += stats.matches;
+        self.totals.bytes_searched += stats.bytes_searched;
+
+        let event = format!(
+            r#"{{"type":"end","data":{{"path":{},"stats":{}}}}}"#,
+            json_data(display_path(&source.path).as_bytes()),
+            json_stats(stats)
+        );
+
+        self.append_event(options, &event);
+    }
+
+    /// Appends the summary event then writes buffer to destination.
+    fn finish(&mut self, options: &Options) {
+        let event = self.summary_event();
+        self.append_event(options, &event);
+
+        if !options.silent {
+            self.writer.write_and_flush();
+        }
+    }
+
+    /// Adds the skipped sources to the summary event, the remaining
+    /// statistics are already part of it.
+    fn report_stats(&mut self, _options: &Options, stats: &SearchStats) -> bool {
+        self.search_stats = Some(*stats);
+        true
+    }
+}
+
+impl JsonPrinter {
+    /// Builds the summary event with statistics summed over every source.
+    fn summary_event(&self) -> String {
+        let elapsed = self.start.elapsed();
+
+        let skipped = match &self.search_stats {
+            Some(search_stats) => format!(
+                r#","skipped":{{"binary":{},"ignored":{},"errors":{}}}"#,
+                search_stats.skipped_binary,
+                search_stats.skipped_ignored,
+                search_stats.skipped_errors
+            ),
+            None => String::new(),
+        };
+
+        format!(
+            concat!(
+                r#"{{"type":"summary","data":{{"elapsed_total":{{"secs":{},"nanos":{},"human":"{:.6}s"}},"#,
+                r#""searches":{},"searches_with_match":{},"stats":{}{}}}}}"#
+            ),
+            elapsed.as_secs(),
+            elapsed.subsec_nanos(),
+            elapsed.as_secs_f64(),
+            self.searches,
+            self.searches_with_match,
+            json_stats(&self.totals),
+            skipped
+        )
+    }
+
+    /// Writes a single event to the buffer followed by a newline.
+    /// Nothing is written in silent mode.
+    fn append_event(&mut self, options: &Options, event: &str) {
+        if options.silent {
+            return;
+        }
+
+        self.writer.push_str(event);
+        self.writer.push('\n');
+    }
+
+    /// Converts an offset into the data of the current source into an offset
+    /// into its bytes.
+    fn source_offset(&self, offset: usize) -> usize {
+        match self.offsets.partition_point(|(data_offset, _)| *data_offset <= offset) {
+            0 => offset,
+            index => {
+                let (data_offset, byte_offset) = self.offsets[index - 1];
+                byte_offset + offset - data_offset
+            }
+        }
+    }
+
+    /// Returns the bytes of the current source that `source.data[start..end]` was read from.
+    fn source_bytes<'a>(&self, source: &'a Source, start: usize, end: usize) -> &'a [u8] {
+        match &source.bytes {
+            Some(bytes) => &bytes[self.source_offset(start)..self.source_offset(end)],
+            None => &source.data.as_bytes()[start..end],
+        }
+    }
+
+    /// Builds a `match` or `context` event for a line. When `show_pattern` is set
+    /// the line and each submatch include their patterns, see [json_pattern].
+    fn line_event(&self, options: &Options, event_type: &str, source: &Source, line: &SinkLine) -> String {
+        let show_pattern = options.show_pattern.is_some();
+        let line_start = self.source_offset(line.byte_offset);
+        let line_bytes = self.source_bytes(source, line.byte_offset, line.byte_offset + line.text.len());
+
+        let submatches: Vec<String> = line.matches.iter()
+            .enumerate()
+            .map(|(index, span)| {
+                let pattern = match line.match_patterns.get(index) {
+                    Some(Some(pattern)) if show_pattern => format!(r#","pattern":{}"#, json_pattern(options, *pattern)),
+                    _ if show_pattern => String::from(r#","pattern":null"#),
+                    _ => String::new(),
+                };
+
+                let start = line.byte_offset + span.start;
+                let end = line.byte_offset + span.end;
+
+                format!(
+                    r#"{{"match":{},"start":{},"end":{}{}}}"#,
+                    json_data(self.source_bytes(source, start, end)),
+                    self.source_offset(start) - line_start,
+                    self.source_offset(end) - line_start,
+                    pattern
+                )
+            })
+            .collect();
+
+        let patterns = if show_pattern {
+            let patterns: Vec<String> = line.patterns.iter()
+                .map(|pattern| json_pattern(options, *pattern))
+                .collect();
+
+            format!(r#","patterns":[{}]"#, patterns.join(","))
+        } else {
+            String::new()
+        };
+
+        format!(
+            concat!(
+                r#"{{"type":"{}","data":{{"path":{},"lines":{},"#,
+                r#""line_number":{},"absolute_offset":{},"submatches":[{}]{}}}}}"#
+            ),
+            event_type,
+            json_data(display_path(&source.path).as_bytes()),
+            json_data(line_bytes),
+            line.line_number,
+            line_start,
+            submatches.join(","),
+            patterns
+        )
+    }
+}
+
+/// Returns the path that should be reported for a source.
+fn display_path(path: &str) -> &str {
+    if path != "-" {
+        path
+    } else {
+        "(standard input)"
+    }
+}
+
+/// Builds a JSON object holding `data`, as text when it is valid UTF-8
+/// and as base64 encoded bytes otherwise.
+fn json_data(data: &[u8]) -> String {
+    match std::str::from_utf8(data) {
+        Ok(text) => format!(r#"{{"text":"{}"}}"#, escape_json(text)),
+        Err(_) => format!(r#"{{"bytes":"{}"}}"#, encode_base64(data)),
+    }
+}
+
+/// Maps offsets into the data of a source that isn't valid UTF-8 to offsets into
+/// its bytes. Holds an offset pair for the start and the end of every replacement
+/// character, offsets in between two pairs differ by the same amount.
+fn offset_map(bytes: &[u8]) -> Vec<(usize, usize)> {
+    let replacement_len = char::REPLACEMENT_CHARACTER.len_utf8();
+    let mut offsets = Vec::new();
+    let (mut data_offset, mut byte_offset) = (0, 0);
+
+    for chunk in bytes.utf8_chunks() {
+        data_offset += chunk.valid().len();
+        byte_offset += chunk.valid().len();
+
+        if !chunk.invalid().is_empty() {
+            offsets.push((data_offset, byte_offset));
+            data_offset += replacement_len;
+            byte_offset += chunk.invalid().len();
+            offsets.push((data_offset, byte_offset));
+        }
+    }
+
+    offsets
+}
+
+/// Builds the JSON value naming the pattern at `index`, its number starting from 1,
+/// or its [label](Options::pattern_label) as a string with [PatternLabel::Pattern].
+fn json_pattern(options: &Options, index: usize) -> String {
+    match options.show_pattern {
+        Some(PatternLabel::Pattern) => format!(r#""{}""#, escape_json(options.pattern_label(index))),
+        _ => (index + 1).to_string(),
+    }
+}
+
+/// Builds a JSON object from search statistics.
+fn json_stats(stats: &SourceStats) -> String {
+    format!(
+        r#"{{"matched_lines":{},"matches":{},"bytes_searched":{}}}"#,
+        stats.matched_lines,
+        stats.matches,
+        stats.bytes_searched
+    )
+}
+
+/// Escapes a string so it can be used inside of a JSON string literal.
+pub fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => {
+                escaped.push_str(&format!("\\u{:04x}", c as u32));
+            },
+            c => escaped.push(c),
+        }
+    }
+
+    escaped
+}
+
+/// Encodes bytes as standard, padded base64.
+fn encode_base64(data: &[u8]) -> String {
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+
+    for chunk in data.chunks(3) {
+        let b0 = chunk[0] as u32;
+        let b1 = chunk.get(1).copied().unwrap_or(0) as u32;
+        let b2 = chunk.get(2).copied().unwrap_or(0) as u32;
+        let triple = (b0 << 16) | (b1 << 8) | b2;
+
+        for i in 0..4 {
+            if i <= chunk.len() {
+                let index = (triple >> (18 - 6 * i)) & 0x3f;
+                encoded.push(BASE64_ALPHABET[index as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
@@ -0,0 +1,166 @@
+#![allow(unused_imports)]
+use regex::Regex;
+
+use crate::matcher;
+
+use super::*;
+
+#[test]
+fn escape_json_strings() {
+    assert_eq!(escape_json("plain text"), "plain text");
+    assert_eq!(escape_json("\"quoted\""), "\\\"quoted\\\"");
+    assert_eq!(escape_json("back\\slash"), "back\\\\slash");
+    assert_eq!(escape_json("tab\tnew\nline\r"), "tab\\tnew\\nline\\r");
+    assert_eq!(escape_json("bell\x07"), "bell\\u0007");
+}
+
+#[test]
+fn encode_bytes_as_base64() {
+    assert_eq!(encode_base64(b""), "");
+    assert_eq!(encode_base64(b"f"), "Zg==");
+    assert_eq!(encode_base64(b"fo"), "Zm8=");
+    assert_eq!(encode_base64(b"foo"), "Zm9v");
+    assert_eq!(encode_base64(b"foobar"), "Zm9vYmFy");
+    assert_eq!(encode_base64(&[0xff, 0xfe, 0x00]), "//4A");
+}
+
+#[test]
+fn json_data_text_and_bytes() {
+    assert_eq!(json_data(b"dew"), r#"{"text":"dew"}"#);
+    assert_eq!(json_data(&[0x64, 0xff]), r#"{"bytes":"ZP8="}"#);
+}
+
+#[test]
+fn maps_offsets_of_invalid_utf8() {
+    // "caf\xe9 dew\n\xff\xfe" is read as "caf\u{fffd} dew\n\u{fffd}\u{fffd}"
+    let offsets = offset_map(b"caf\xe9 dew\n\xff\xfe");
+    assert_eq!(offsets, vec!((3, 3), (6, 4), (11, 9), (14, 10), (14, 10), (17, 11)));
+    assert!(offset_map(b"dew\n").is_empty());
+}
+
+#[test]
+fn appends_latin1_lines_as_bytes() {
+    let mut source = Source::new(String::from("res/test/latin1.txt"));
+    let options = Options {
+        patterns: vec!(String::from("dew")),
+        ..Default::default()
+    };
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    assert!(source.read_data().is_err());
+    source.read_data_lossy().unwrap();
+
+    let mut json_printer = JsonPrinter {
+        writer: BufferedWriter::for_test(),
+        ..Default::default()
+    };
+
+    matcher::search_source(&regex, &options, &source, &mut json_printer);
+
+    let expected_buffer = String::from(concat!(
+        r#"{"type":"begin","data":{"path":{"text":"res/test/latin1.txt"}}}"#, "\n",
+        r#"{"type":"match","data":{"path":{"text":"res/test/latin1.txt"},"lines":{"bytes":"Y2Fm6SBkZXc="},"#,
+        r#""line_number":1,"absolute_offset":0,"submatches":[{"match":{"text":"dew"},"start":5,"end":8}]}}"#, "\n",
+        r#"{"type":"match","data":{"path":{"text":"res/test/latin1.txt"},"lines":{"text":"plain dew"},"#,
+        r#""line_number":2,"absolute_offset":9,"submatches":[{"match":{"text":"dew"},"start":6,"end":9}]}}"#, "\n",
+        r#"{"type":"end","data":{"path":{"text":"res/test/latin1.txt"},"#,
+        r#""stats":{"matched_lines":2,"matches":2,"bytes_searched":19}}}"#, "\n",
+    ));
+
+    assert_eq!(json_printer.writer.as_str(), expected_buffer);
+}
+
+#[test]
+fn appends_source_events_to_buffer() {
+    let mut source = Source::new(String::from("res/test/haiku.txt"));
+    let options = Options {
+        patterns: vec!(String::from("dew")),
+        ..Default::default()
+    };
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    source.read_data().unwrap();
+
+    let mut json_printer = JsonPrinter {
+        writer: BufferedWriter::for_test(),
+        ..Default::default()
+    };
+
+    matcher::search_source(&regex, &options, &source, &mut json_printer);
+
+    let expected_buffer = String::from(concat!(
+        r#"{"type":"begin","data":{"path":{"text":"res/test/haiku.txt"}}}"#, "\n",
+        r#"{"type":"match","data":{"path":{"text":"res/test/haiku.txt"},"lines":{"text":"This world of dew,"},"#,
+        r#""line_number":1,"absolute_offset":0,"submatches":[{"match":{"text":"dew"},"start":14,"end":17}]}}"#, "\n",
+        r#"{"type":"match","data":{"path":{"text":"res/test/haiku.txt"},"lines":{"text":"is a world of dew,"},"#,
+        r#""line_number":2,"absolute_offset":19,"submatches":[{"match":{"text":"dew"},"start":14,"end":17}]}}"#, "\n",
+        r#"{"type":"end","data":{"path":{"text":"res/test/haiku.txt"},"#,
+        r#""stats":{"matched_lines":2,"matches":2,"bytes_searched":55}}}"#, "\n",
+    ));
+
+    assert_eq!(json_printer.writer.as_str(), expected_buffer);
+    assert_eq!(json_printer.searches, 1);
+    assert_eq!(json_printer.searches_with_match, 1);
+}
+
+#[test]
+fn summary_includes_skipped_sources() {
+    let options = Options::default();
+    let mut json_printer = JsonPrinter::default();
+
+    assert!(!json_printer.summary_event().contains("skipped"));
+
+    let search_stats = SearchStats { skipped_binary: 1, skipped_errors: 2, ..SearchStats::default() };
+    assert!(json_printer.report_stats(&options, &search_stats));
+
+    let summary = json_printer.summary_event();
+    assert!(summary.starts_with(r#"{"type":"summary","data":{"elapsed_total":"#));
+    assert!(summary.ends_with(concat!(
+        r#""searches":0,"searches_with_match":0,"stats":{"matched_lines":0,"matches":0,"bytes_searched":0},"#,
+        r#""skipped":{"binary":1,"ignored":0,"errors":2}}}"#
+    )));
+}
+
+#[test]
+fn match_events_include_patterns() {
+    let mut source = Source::new(String::from("res/test/haiku.txt"));
+    let mut options = Options::default();
+    options.patterns = vec!(String::from("dew"), String::from("world"));
+    options.show_pattern = Some(options::PatternLabel::Index);
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+    let pattern_set = matcher::PatternSet::new(&options).unwrap();
+
+    source.read_data().unwrap();
+
+    let mut json_printer = JsonPrinter {
+        writer: BufferedWriter::for_test(),
+        ..Default::default()
+    };
+
+    matcher::search_source_with_patterns(&regex, Some(&pattern_set), &options, &source, &mut json_printer);
+
+    let match_event = json_printer.writer.as_str().lines().nth(1).unwrap();
+    assert!(match_event.ends_with(concat!(
+        r#""submatches":[{"match":{"text":"world"},"start":5,"end":10,"pattern":2},"#,
+        r#"{"match":{"text":"dew"},"start":14,"end":17,"pattern":1}],"patterns":[1,2]}}"#
+    )));
+
+    options.show_pattern = Some(options::PatternLabel::Pattern);
+    options.pattern_labels = vec!(Some(String::from("D")));
+
+    let mut json_printer = JsonPrinter {
+        writer: BufferedWriter::for_test(),
+        ..Default::default()
+    };
+
+    matcher::search_source_with_patterns(&regex, Some(&pattern_set), &options, &source, &mut json_printer);
+
+    let match_event = json_printer.writer.as_str().lines().nth(1).unwrap();
+    assert!(match_event.ends_with(concat!(
+        r#""submatches":[{"match":{"text":"world"},"start":5,"end":10,"pattern":"world"},"#,
+        r#"{"match":{"text":"dew"},"start":14,"end":17,"pattern":"D"}],"patterns":["D","world"]}}"#
+    )));
+}
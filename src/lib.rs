@@ -56,16 +56,51 @@ pub mod options;
 pub mod output;
 /// Handles colors used for output highlights
 pub mod colors;
+/// Defines the events produced by a search and the [Sink](sink::Sink) trait that receives them.
+pub mod sink;
+/// Writes results as JSON Lines.
+pub mod json;
+/// Applies replacements to sources in place or writes them as a unified diff.
+pub mod editor;
+/// Builds OSC 8 hyperlinks for file names in output.
+pub mod hyperlink;
+/// Writes results as a SARIF log for code scanning tools.
+pub mod sarif;
+/// Enforces rules read from a rules file.
+pub mod lint;
+/// Compares matches with a baseline of previously known matches.
+pub mod baseline;
+/// Writes results with a custom output template.
+pub mod template;
+/// Extracts capture groups of matches as CSV or TSV tables.
+pub mod extract;
+/// Counts how often distinct matches occur.
+pub mod tally;
 
+use baseline::BaselineFilter;
+use colors::Colors;
 use config::Config;
+use editor::Editor;
+use extract::Extractor;
+use json::JsonPrinter;
+use lint::{Linter, Severity};
+use matcher::PatternSet;
+use options::{Options, OutputFormat};
 use output::OutputBuffer;
-use regex::{Regex, Matches};
+use sarif::SarifPrinter;
+use regex::Regex;
+use sink::{SearchStats, Sink};
+use tally::Tally;
+use template::TemplatePrinter;
+use source::Source;
 use std::error::Error;
 use std::io::{stderr, Write, ErrorKind};
+use std::time::Instant;
 
 /// Runs grepRS with command line arguments.
 /// #### Param:
 /// *   args - Vector of CL arguments.
+///
 /// See [Config] for more information about run configuration.
 pub fn run(args: Vec<String>) -> Result<(), Box<dyn Error>> {
     if args.len() < 2 {
@@ -73,13 +108,103 @@ pub fn run(args: Vec<String>) -> Result<(), Box<dyn Error>> {
     }
 
     let config: Config = Config::new(args)?;
-    let regex: Regex = matcher::build_regex(&config.options)?;
-    let mut output_buffer = OutputBuffer::default();
-
     let options = &config.options;
 
-    for mut source in config.sources {
-        if let Err(io_err) = source.read_data() {
+    if options.debug {
+        for warning in Colors::get_color_warnings() {
+            print_debug_msg(&warning);
+        }
+    }
+
+    if let Some(rules_path) = &options.rules {
+        let mut linter = Linter::load(rules_path)?;
+        let regex: Regex = linter.build_regex()?;
+
+        search_sources(&regex, None, options, config.sources, &mut linter)?;
+
+        return match linter.findings(Severity::Error) {
+            0 => Ok(()),
+            errors => Err(Box::from(format!("{} error(s) reported by rules!", errors))),
+        };
+    }
+
+    let regex: Regex = matcher::build_regex(options)?;
+
+    let pattern_set = if options.show_pattern.is_some() || options.uses_pattern_colors() {
+        Some(PatternSet::new(options)?)
+    } else {
+        None
+    };
+
+    let mut sink: Box<dyn Sink> = if options.diff || options.in_place.is_some() {
+        Box::new(Editor::default())
+    } else if let Some(format) = options.extract {
+        Box::new(Extractor::new(options, format, regex.clone())?)
+    } else if let Some(group) = &options.tally {
+        Box::new(Tally::new(group, regex.clone())?)
+    } else {
+        match &options.output_format {
+            OutputFormat::Text => Box::new(OutputBuffer::default()),
+            OutputFormat::Json => Box::new(JsonPrinter::default()),
+            OutputFormat::Sarif => Box::new(SarifPrinter::new(options)?),
+            OutputFormat::Template(template) => Box::new(TemplatePrinter::new(template.clone(), regex.clone())),
+        }
+    };
+
+    search_sources(&regex, pattern_set.as_ref(), options, config.sources, sink.as_mut())
+}
+
+/// Reads and searches each source, reporting results to `sink`. Matches are
+/// compared with baselines when one is used or written, see [BaselineFilter].
+/// Selected lines are annotated with the patterns of `pattern_set` that match them.
+fn search_sources(
+    regex: &Regex,
+    pattern_set: Option<&PatternSet>,
+    options: &Options,
+    sources: Vec<Source>,
+    sink: &mut dyn Sink
+) -> Result<(), Box<dyn Error>> {
+    if options.baseline.is_none() && options.write_baseline.is_none() {
+        search_each_source(regex, pattern_set, options, sources, sink);
+        return Ok(());
+    }
+
+    let mut filter = BaselineFilter::new(sink, options.baseline.as_deref())?;
+    search_each_source(regex, pattern_set, options, sources, &mut filter);
+
+    if let Some(path) = &options.write_baseline {
+        filter.write_baseline(path)?;
+    }
+
+    Ok(())
+}
+
+/// Reads and searches each source, reporting results to `sink`. Sources that
+/// can't be read are skipped. Statistics of the run are reported when
+/// `stats` is set, see [SearchStats].
+fn search_each_source(
+    regex: &Regex,
+    pattern_set: Option<&PatternSet>,
+    options: &Options,
+    sources: Vec<Source>,
+    sink: &mut dyn Sink
+) {
+    let start = Instant::now();
+    let mut search_stats = SearchStats::default();
+
+    // JSON output writes lines that aren't valid UTF-8 as bytes instead of skipping their source
+    let read_lossy = options.output_format == OutputFormat::Json;
+
+    for mut source in sources {
+        let read_result = if read_lossy {
+            source.read_data_lossy()
+        } else {
+            source.read_data()
+        };
+
+        if let Err(io_err) = read_result {
+            search_stats.add_skipped(&io_err);
+
             if !options.no_messages {
                 print_io_err_msg(io_err, &source.path);
             }
@@ -87,32 +212,54 @@ pub fn run(args: Vec<String>) -> Result<(), Box<dyn Error>> {
             continue;
         };
 
-        if options.count_lines {
-            // COUNT MATCHING LINES
-            let source_counts: usize = matcher::count_matching_lines(
-                &regex,
-                &source.data,
-                options.invert_match
-            );
-
-            output_buffer.append_source_counts(options, &source, source_counts);
-        }
-        else {
-            // SEARCH FOR MATCHES IN LINES
-            let matches: Vec<Matches> = matcher::search_lines(
-                &regex,
-                &source.data
-            );
-
-            output_buffer.append_source_matches(options, &source, matches);
-        }
+        let stats = matcher::search_source_with_patterns(regex, pattern_set, options, &source, sink);
+        search_stats.add_source(&stats);
     }
 
-    if !options.silent {
-        output_buffer.write_and_flush();
+    search_stats.elapsed = start.elapsed();
+
+    let stats_reported = options.stats && sink.report_stats(options, &search_stats);
+
+    sink.finish(options);
+
+    if options.stats && !stats_reported {
+        print_stats(&search_stats);
     }
+}
 
-    return Ok(());
+/// Writes statistics about the entire run to stderr.
+fn print_stats(stats: &SearchStats) {
+    writeln!(
+        stderr(),
+        concat!(
+            "\n{} sources searched\n",
+            "{} sources with matches\n",
+            "{} matched lines\n",
+            "{} matches\n",
+            "{} bytes searched\n",
+            "{} sources skipped ({} binary, {} ignored, {} errors)\n",
+            "{:.6} seconds elapsed",
+        ),
+        stats.searches,
+        stats.searches_with_match,
+        stats.totals.matched_lines,
+        stats.totals.matches,
+        stats.totals.bytes_searched,
+        stats.skipped(),
+        stats.skipped_binary,
+        stats.skipped_ignored,
+        stats.skipped_errors,
+        stats.elapsed.as_secs_f64(),
+    ).expect("grepRS: could not write to stderr!");
+}
+
+/// Prints a diagnostic message to stderr.
+fn print_debug_msg(msg: &str) {
+    writeln!(
+        stderr(),
+        "grepRS: warning: {}",
+        msg,
+    ).expect("grepRS: could not write to stderr!");
 }
 
 /// Prints a message to stderr explaining an IO error.
@@ -131,8 +278,7 @@ fn print_io_err_msg(io_err: Box<std::io::Error>, path: &str) {
 
     writeln!(
         stderr(),
-        "{} {}",
-        "grepRS:",
+        "grepRS: {}",
         err_msg,
     ).expect("grepRS: could not write to stderr!");
 }
\ No newline at end of file
@@ -0,0 +1,347 @@
+mod test;
+
+use crate::options;
+use crate::output;
+use crate::sink;
+use crate::source;
+
+use options::Options;
+use output::BufferedWriter;
+use regex::{Regex, RegexSet};
+use sink::{Sink, SinkLine, SourceStats};
+use source::Source;
+use std::error::Error;
+use std::fmt;
+
+/// Marker of comments that suppress rules on their line, followed by rule ids.
+const IGNORE_MARKER: &str = "greprs-ignore:";
+
+/// #### Severity of a rule.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Severity {
+    /// Findings fail the run.
+    Error,
+    /// Findings are reported without failing the run.
+    Warning,
+    /// Findings are informational.
+    Info,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// #### A rule enforced in lint mode.
+///
+/// See [parse_rules] for how rules are defined.
+#[derive(Debug, Clone)]
+pub struct Rule {
+    /// Identifies the rule in output and suppression comments.
+    pub id: String,
+    /// Matches of the pattern are reported as findings.
+    pub pattern: Regex,
+    /// Explains findings of the rule.
+    pub message: String,
+    /// Severity of findings. Defaults to [Severity::Warning].
+    pub severity: Severity,
+    /// The rule only applies to paths matching one of these globs. Applies to every
+    /// path when empty.
+    pub include: Vec<Glob>,
+    /// The rule doesn't apply to paths matching any of these globs.
+    pub exclude: Vec<Glob>,
+}
+
+impl Rule {
+    /// Checks if the rule applies to the source at `path`.
+    pub fn applies_to(&self, path: &str) -> bool {
+        let included = self.include.is_empty() || self.include.iter().any(|glob| glob.is_match(path));
+
+        included && !self.exclude.iter().any(|glob| glob.is_match(path))
+    }
+}
+
+/// #### A glob used to select paths.
+///
+/// `*` matches anything but `/`, `?` matches a single character except `/` and
+/// `**` matches across directories. Globs without a `/` are matched against
+/// file names, other globs against the entire path.
+#[derive(Debug, Clone)]
+pub struct Glob {
+    /// The glob as it was written.
+    pub glob: String,
+    /// Regex equivalent of the glob.
+    regex: Regex,
+}
+
+impl Glob {
+    /// Compiles a glob.
+    pub fn new(glob: &str) -> Result<Glob, Box<dyn Error>> {
+        let mut pattern = String::from("^");
+        let mut chars = glob.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '*' if chars.peek() == Some(&'*') => {
+                    chars.next();
+
+                    if chars.peek() == Some(&'/') {
+                        chars.next();
+                        pattern.push_str("(?:.*/)?");
+                    } else {
+                        pattern.push_str(".*");
+                    }
+                },
+                '*' => pattern.push_str("[^/]*"),
+                '?' => pattern.push_str("[^/]"),
+                c => pattern.push_str(&regex::escape(&c.to_string())),
+            }
+        }
+
+        pattern.push('$');
+
+        Ok(Glob {
+            glob: String::from(glob),
+            regex: Regex::new(&pattern)?,
+        })
+    }
+
+    /// Checks if `path` is selected by the glob.
+    pub fn is_match(&self, path: &str) -> bool {
+        let path = path.strip_prefix("./").unwrap_or(path);
+
+        if self.glob.contains('/') {
+            self.regex.is_match(path)
+        } else {
+            self.regex.is_match(path.rsplit('/').next().unwrap_or(path))
+        }
+    }
+}
+
+/// Parses rules from the contents of a rules file. Each rule starts with its id
+/// in brackets, followed by `key = value` lines:
+/// ```text
+/// # comments start with a '#'
+/// [no-unwrap]
+/// pattern = \.unwrap\(\)
+/// message = avoid unwrap() in production code
+/// severity = error
+/// include = src/**/*.rs
+/// exclude = **/test.rs
+/// ```
+/// `pattern` is required. `severity` is one of `error`, `warning` or `info`.
+/// `include` and `exclude` can be given multiple times.
+pub fn parse_rules(text: &str) -> Result<Vec<Rule>, Box<dyn Error>> {
+    let mut rules: Vec<Rule> = Vec::new();
+    let mut current: Option<(String, Vec<(String, String)>)> = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        if let Some(id) = line.strip_prefix('[').and_then(|line| line.strip_suffix(']')) {
+            if let Some((id, entries)) = current.take() {
+                rules.push(build_rule(id, entries)?);
+            }
+
+            let id = id.trim();
+            if id.is_empty() || rules.iter().any(|rule| rule.id == id) {
+                return Err(Box::from(format!("Invalid rule id on line {}: {}", index + 1, id)));
+            }
+
+            current = Some((String::from(id), Vec::new()));
+            continue;
+        }
+
+        let Some((_, entries)) = current.as_mut() else {
+            return Err(Box::from(format!("Expected a rule id on line {}: {}", index + 1, line)));
+        };
+
+        let Some((key, value)) = line.split_once('=') else {
+            return Err(Box::from(format!("Expected key = value on line {}: {}", index + 1, line)));
+        };
+
+        entries.push((String::from(key.trim()), String::from(value.trim())));
+    }
+
+    if let Some((id, entries)) = current {
+        rules.push(build_rule(id, entries)?);
+    }
+
+    Ok(rules)
+}
+
+/// Builds a rule from the entries listed under its id.
+fn build_rule(id: String, entries: Vec<(String, String)>) -> Result<Rule, Box<dyn Error>> {
+    let mut pattern = None;
+    let mut message = None;
+    let mut severity = Severity::Warning;
+    let mut include = Vec::new();
+    let mut exclude = Vec::new();
+
+    for (key, value) in entries {
+        match key.as_str() {
+            "pattern" => pattern = Some(Regex::new(&value)?),
+            "message" => message = Some(value),
+            "severity" => {
+                severity = match value.as_str() {
+                    "error" => Severity::Error,
+                    "warning" => Severity::Warning,
+                    "info" => Severity::Info,
+                    _ => return Err(Box::from(format!("Unknown severity for rule {}: {}", id, value))),
+                };
+            },
+            "include" => include.push(Glob::new(&value)?),
+            "exclude" => exclude.push(Glob::new(&value)?),
+            _ => return Err(Box::from(format!("Unknown key for rule {}: {}", id, key))),
+        }
+    }
+
+    let Some(pattern) = pattern else {
+        return Err(Box::from(format!("Rule {} has no pattern!", id)));
+    };
+
+    let message = message.unwrap_or_else(|| format!("matches the pattern \"{}\"", pattern.as_str()));
+
+    Ok(Rule { id, pattern, message, severity, include, exclude })
+}
+
+/// Checks if a suppression comment on `line` names the rule `id`.
+fn is_suppressed(line: &str, id: &str) -> bool {
+    line.match_indices(IGNORE_MARKER).any(|(index, _)| {
+        line[index + IGNORE_MARKER.len()..]
+            .split(|c: char| c == ',' || c.is_whitespace())
+            .filter(|name| !name.is_empty())
+            .any(|name| name == id)
+    })
+}
+
+/// Reports findings of [rules](Rule) as `path:line:column: severity[id]: message`.
+///
+/// Lines are selected by a single pass of [build_regex](Linter::build_regex) over each
+/// source, then every rule that applies to the source is checked against selected lines.
+/// Columns are 1-based byte offsets. Findings are suppressed by a `greprs-ignore: id`
+/// comment on the same line.
+pub struct Linter {
+    /// Buffers findings and writes them to stdout.
+    writer: BufferedWriter,
+    /// Rules to enforce.
+    rules: Vec<Rule>,
+    /// Patterns of every rule, finds the rules matching a line at once.
+    rule_set: RegexSet,
+    /// If each rule applies to the current source.
+    applicable: Vec<bool>,
+    /// Number of findings of each severity, in the order error, warning, info.
+    findings: [usize; 3],
+}
+
+impl Linter {
+    /// Creates new instance of Linter enforcing `rules` with stdout as destination.
+    pub fn new(rules: Vec<Rule>) -> Result<Linter, Box<dyn Error>> {
+        let rule_set = RegexSet::new(rules.iter().map(|rule| rule.pattern.as_str()))?;
+
+        Ok(Linter {
+            writer: BufferedWriter::default(),
+            applicable: vec![true; rules.len()],
+            rules,
+            rule_set,
+            findings: [0; 3],
+        })
+    }
+
+    /// Reads and parses rules from the file at `path`, see [parse_rules].
+    pub fn load(path: &str) -> Result<Linter, Box<dyn Error>> {
+        let mut rules_source = Source::new(String::from(path));
+        rules_source.read_data()
+            .map_err(|io_err| format!("Could not read rules file {}: {}", path, io_err))?;
+
+        Self::new(parse_rules(&rules_source.data)?)
+    }
+
+    /// Builds a regex that selects every line matched by at least one rule.
+    pub fn build_regex(&self) -> Result<Regex, Box<dyn Error>> {
+        let patterns: Vec<String> = self.rules.iter()
+            .map(|rule| format!("(?:{})", rule.pattern.as_str()))
+            .collect();
+
+        Ok(Regex::new(&patterns.join("|"))?)
+    }
+
+    /// Returns the number of findings of `severity`.
+    pub fn findings(&self, severity: Severity) -> usize {
+        self.findings[severity as usize]
+    }
+
+}
+
+impl Sink for Linter {
+    fn begin_source(&mut self, _options: &Options, source: &Source) {
+        self.applicable = self.rules.iter()
+            .map(|rule| rule.applies_to(&source.path))
+            .collect();
+    }
+
+    /// Writes a finding for every match of every rule on the line, ordered by column.
+    fn matched_line(&mut self, options: &Options, source: &Source, line: &SinkLine) {
+        let mut line_findings: Vec<(usize, usize)> = Vec::new();
+
+        for index in self.rule_set.matches(line.text).iter() {
+            let rule = &self.rules[index];
+
+            if !self.applicable[index] || is_suppressed(line.text, &rule.id) {
+                continue;
+            }
+
+            for match_obj in rule.pattern.find_iter(line.text) {
+                line_findings.push((match_obj.start() + 1, index));
+            }
+        }
+
+        line_findings.sort();
+
+        let path = if source.path != "-" {
+            source.path.as_str()
+        } else {
+            "(standard input)"
+        };
+
+        for (column, index) in line_findings {
+            let rule = &self.rules[index];
+            self.findings[rule.severity as usize] += 1;
+
+            if options.silent {
+                continue;
+            }
+
+            self.writer.push_str(&format!(
+                "{}:{}:{}: {}[{}]: {}\n",
+                path,
+                line.line_number,
+                column,
+                rule.severity,
+                rule.id,
+                rule.message
+            ));
+        }
+    }
+
+    fn context_line(&mut self, _options: &Options, _source: &Source, _line: &SinkLine) {}
+
+    fn end_source(&mut self, _options: &Options, _source: &Source, _stats: &SourceStats) {}
+
+    fn finish(&mut self, options: &Options) {
+        if !options.silent {
+            self.writer.write_and_flush();
+        }
+    }
+}
@@ -0,0 +1,131 @@
+#![allow(unused_imports)]
+use crate::matcher;
+
+use super::*;
+
+#[test]
+fn glob_matching() {
+    let glob = Glob::new("*.rs").unwrap();
+    assert!(glob.is_match("main.rs"));
+    assert!(glob.is_match("src/lint/mod.rs"));
+    assert!(!glob.is_match("src/lint/mod.rs.bak"));
+
+    let glob = Glob::new("src/*.rs").unwrap();
+    assert!(glob.is_match("./src/lib.rs"));
+    assert!(!glob.is_match("src/lint/mod.rs"));
+
+    let glob = Glob::new("src/**/test.rs").unwrap();
+    assert!(glob.is_match("src/test.rs"));
+    assert!(glob.is_match("src/lint/test.rs"));
+    assert!(!glob.is_match("tests/test.rs"));
+
+    let glob = Glob::new("res/te?t/**").unwrap();
+    assert!(glob.is_match("res/test/haiku.txt"));
+    assert!(!glob.is_match("res/te/t/haiku.txt"));
+}
+
+#[test]
+fn parse_rules_file() {
+    let rules = parse_rules(concat!(
+        "# banned constructs\n",
+        "[no-unwrap]\n",
+        "pattern = \\.unwrap\\(\\)\n",
+        "message = avoid unwrap() in production code\n",
+        "severity = error\n",
+        "include = src/**/*.rs\n",
+        "exclude = **/test.rs\n",
+        "\n",
+        "[todo]\n",
+        "pattern = TODO[^(]\n",
+    )).unwrap();
+
+    assert_eq!(rules.len(), 2);
+    assert_eq!(rules[0].id, "no-unwrap");
+    assert_eq!(rules[0].message, "avoid unwrap() in production code");
+    assert_eq!(rules[0].severity, Severity::Error);
+    assert!(rules[0].applies_to("src/lint/mod.rs"));
+    assert!(!rules[0].applies_to("src/lint/test.rs"));
+    assert!(!rules[0].applies_to("build.rs"));
+
+    assert_eq!(rules[1].id, "todo");
+    assert_eq!(rules[1].message, "matches the pattern \"TODO[^(]\"");
+    assert_eq!(rules[1].severity, Severity::Warning);
+    assert!(rules[1].applies_to("build.rs"));
+}
+
+#[test]
+fn parse_invalid_rules() {
+    assert!(parse_rules("pattern = x\n").is_err());
+    assert!(parse_rules("[a]\nmessage = no pattern\n").is_err());
+    assert!(parse_rules("[a]\npattern = x\nseverity = fatal\n").is_err());
+    assert!(parse_rules("[a]\npattern = x\ncolor = red\n").is_err());
+    assert!(parse_rules("[a]\npattern = x\n[a]\npattern = y\n").is_err());
+    assert!(parse_rules("[a]\npattern\n").is_err());
+    assert!(parse_rules("[a]\npattern = (\n").is_err());
+}
+
+#[test]
+fn suppression_comments() {
+    assert!(is_suppressed("x.unwrap(); // greprs-ignore: no-unwrap", "no-unwrap"));
+    assert!(is_suppressed("x.unwrap(); /* greprs-ignore: todo, no-unwrap */", "no-unwrap"));
+    assert!(!is_suppressed("x.unwrap(); // greprs-ignore: no-unwrap-extra", "no-unwrap"));
+    assert!(!is_suppressed("x.unwrap();", "no-unwrap"));
+}
+
+#[test]
+fn reports_findings() {
+    let mut linter = Linter::load("res/test/lint.rules").unwrap();
+    linter.writer = BufferedWriter::for_test();
+
+    let regex = linter.build_regex().unwrap();
+    let options = Options::default();
+
+    let mut haiku = Source::new(String::from("res/test/haiku.txt"));
+    haiku.read_data().unwrap();
+
+    let other = Source {
+        path: String::from("notes.md"),
+        data: String::from("dew and yet\nyet dew, greprs-ignore: yet-again\n"),
+        bytes: None,
+    };
+
+    matcher::search_source(&regex, &options, &haiku, &mut linter);
+    matcher::search_source(&regex, &options, &other, &mut linter);
+
+    let expected_buffer = concat!(
+        "res/test/haiku.txt:1:15: error[no-dew]: dew is not allowed\n",
+        "res/test/haiku.txt:2:15: error[no-dew]: dew is not allowed\n",
+        "notes.md:1:9: warning[yet-again]: avoid repeating yet\n",
+    );
+
+    assert_eq!(linter.writer.as_str(), expected_buffer);
+    assert_eq!(linter.findings(Severity::Error), 2);
+    assert_eq!(linter.findings(Severity::Warning), 1);
+    assert_eq!(linter.findings(Severity::Info), 0);
+}
+
+#[test]
+fn orders_findings_by_column() {
+    let rules = parse_rules("[b]\npattern = b\n[a]\npattern = a\nseverity = info\n").unwrap();
+    let mut linter = Linter::new(rules).unwrap();
+    linter.writer = BufferedWriter::for_test();
+
+    let regex = linter.build_regex().unwrap();
+    let options = Options::default();
+    let source = Source {
+        path: String::from("-"),
+        data: String::from("abab\n"),
+        bytes: None,
+    };
+
+    matcher::search_source(&regex, &options, &source, &mut linter);
+
+    let expected_buffer = concat!(
+        "(standard input):1:1: info[a]: matches the pattern \"a\"\n",
+        "(standard input):1:2: warning[b]: matches the pattern \"b\"\n",
+        "(standard input):1:3: info[a]: matches the pattern \"a\"\n",
+        "(standard input):1:4: warning[b]: matches the pattern \"b\"\n",
+    );
+
+    assert_eq!(linter.writer.as_str(), expected_buffer);
+}
@@ -16,8 +16,7 @@ fn main() {
         Err(msg) => {
             writeln!(
                 &mut stderr,
-                "{} {}",
-                "Error encountered while running grepRS.",
+                "Error encountered while running grepRS. {}",
                 msg
             ).expect("Could not write to stderr.");
 
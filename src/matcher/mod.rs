@@ -1,10 +1,14 @@
 mod test;
 
 use crate::options;
+use crate::sink;
+use crate::source;
 
 use options::Options;
-use regex::Matches;
-use regex::Regex;
+use regex::{Captures, Matches};
+use regex::{Regex, RegexSet};
+use sink::{Sink, SinkLine, SourceStats, Span};
+use source::Source;
 use std::error::Error;
 
 /// Constructs regular expression from options.
@@ -19,31 +23,420 @@ pub fn build_regex(options: &Options) -> Result<Regex, Box<dyn Error>> {
     Ok(regex)
 }
 
+/// Constructs one regular expression per pattern, using the same flags and
+/// line or word matching as [build_regex].
+pub fn build_pattern_regexes(options: &Options) -> Result<Vec<Regex>, Box<dyn Error>> {
+    let flags = build_flags(options);
+
+    let mut regexes = Vec::new();
+    for pattern in build_patterns(options) {
+        regexes.push(Regex::new(&format!(r"{}{}", flags, pattern))?);
+    }
+
+    Ok(regexes)
+}
+
+/// Finds which of several patterns match, for [Options::show_pattern] and
+/// [pattern colors](Options::pattern_color).
+///
+/// [build_regex] combines every pattern into a single alternation, which only
+/// reports the leftmost pattern of each match. A pattern set evaluates all
+/// patterns at once instead.
+#[derive(Clone, Debug)]
+pub struct PatternSet {
+    /// Every pattern, used to find the patterns that match a line.
+    set: RegexSet,
+    /// One regex per pattern, see [build_pattern_regexes].
+    regexes: Vec<Regex>,
+}
+
+impl PatternSet {
+    /// Constructs a pattern set from the patterns of `options`, using the same
+    /// flags and line or word matching as [build_regex].
+    pub fn new(options: &Options) -> Result<PatternSet, Box<dyn Error>> {
+        let flags = build_flags(options);
+        let set = RegexSet::new(build_patterns(options).iter().map(|pattern| format!(r"{}{}", flags, pattern)))?;
+
+        Ok(PatternSet { set, regexes: build_pattern_regexes(options)? })
+    }
+
+    /// Sets the patterns of `line` and of each of its matches. A span continuing
+    /// a multiline match gets `continued_pattern`, the pattern of the match it continues.
+    pub fn annotate(&self, options: &Options, source: &Source, line: &mut SinkLine, continued_pattern: Option<usize>) {
+        line.match_patterns = line.matches.iter()
+            .enumerate()
+            .map(|(index, span)| if index == 0 && line.continues_match {
+                continued_pattern
+            } else {
+                matching_pattern(&self.regexes, options, source, line, span)
+            })
+            .collect();
+
+        line.patterns = if options.multiline {
+            let mut patterns: Vec<usize> = line.match_patterns.iter().flatten().copied().collect();
+            patterns.sort_unstable();
+            patterns.dedup();
+            patterns
+        } else {
+            self.set.matches(line.text).into_iter().collect()
+        };
+    }
+}
+
+/// Finds the index of the pattern that produced the match at `span` of `line`.
+/// `regexes` are the regexes of [build_pattern_regexes], the first pattern that
+/// matches where the match starts is chosen, like the alternation of [build_regex].
+/// Returns `None` if no pattern matches there, e.g. for lines continuing a multiline match.
+pub fn matching_pattern(
+    regexes: &[Regex],
+    options: &Options,
+    source: &Source,
+    line: &SinkLine,
+    span: &Span
+) -> Option<usize> {
+    let (haystack, start) = match_haystack(options, source, line, span);
+
+    regexes.iter().position(|regex| {
+        regex.find_at(haystack, start).is_some_and(|match_obj| match_obj.start() == start)
+    })
+}
+
+/// Finds the capture groups of the match at `span` of `line`.
+/// Returns `None` if `regex` doesn't match there, e.g. for lines continuing a multiline match.
+pub fn match_captures<'h>(
+    regex: &Regex,
+    options: &Options,
+    source: &'h Source,
+    line: &SinkLine<'h>,
+    span: &Span
+) -> Option<Captures<'h>> {
+    let (haystack, start) = match_haystack(options, source, line, span);
+
+    regex.captures_at(haystack, start)
+        .filter(|captures| captures.get(0).is_some_and(|match_obj| match_obj.start() == start))
+}
+
+/// Returns the text a match at `span` of `line` was found in and the byte offset of the
+/// match inside of it. Multiline matches are found in the entire source.
+fn match_haystack<'h>(options: &Options, source: &'h Source, line: &SinkLine<'h>, span: &Span) -> (&'h str, usize) {
+    if options.multiline {
+        (source.data.as_str(), line.byte_offset + span.start)
+    } else {
+        (line.text, span.start)
+    }
+}
+
+/// A line of a source, or a record when a record separator is set.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Record<'a> {
+    /// Byte offset of the start of the record inside its source.
+    pub byte_offset: usize,
+    /// Content of the record, without its terminator.
+    pub text: &'a str,
+    /// The terminator or separator that followed the record. Empty for a final
+    /// record without one.
+    pub terminator: &'a str,
+}
+
+/// Matches found inside of a single line of a source.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+struct LineMatches {
+    /// If any match touches the line.
+    touched: bool,
+    /// Spans of matches inside of the line.
+    matches: Vec<Span>,
+    /// Replacements for each of `matches` when a replacement template is set.
+    replacements: Vec<String>,
+    /// If the first of `matches` continues a match that started on a previous line.
+    continues_match: bool,
+}
+
+/// Searches a source line by line and reports the results to `sink`.
+/// See [split_records] for how sources are split into lines. When `multiline`
+/// is set the pattern is matched against the entire source instead and every
+/// line touched by a match is selected.
+/// Returns the statistics that were reported to the sink for the source.
+pub fn search_source(
+    regex: &Regex,
+    options: &Options,
+    source: &Source,
+    sink: &mut dyn Sink
+) -> SourceStats {
+    search_source_with_patterns(regex, None, options, source, sink)
+}
+
+/// Searches a source like [search_source], selected lines are annotated with
+/// the patterns of `pattern_set` that match them when one is given.
+pub fn search_source_with_patterns(
+    regex: &Regex,
+    pattern_set: Option<&PatternSet>,
+    options: &Options,
+    source: &Source,
+    sink: &mut dyn Sink
+) -> SourceStats {
+    // pattern of the last match reported, carried over to lines continuing it
+    let mut continued_pattern = None;
+    let mut stats = SourceStats {
+        bytes_searched: source.bytes.as_ref().map_or(source.data.len(), Vec::len),
+        ..SourceStats::default()
+    };
+
+    sink.begin_source(options, source);
+
+    let records = split_records(&source.data, options);
+
+    let mut multiline_matches = if options.multiline {
+        let (line_matches, num_matches) = search_multiline(regex, options, &source.data, &records);
+        stats.matches += num_matches;
+
+        Some(line_matches.into_iter())
+    } else {
+        None
+    };
+
+    for (index, record) in records.into_iter().enumerate() {
+        let line = record.text;
+        let mut line_matches = match &mut multiline_matches {
+            Some(multiline_matches) => multiline_matches.next().unwrap_or_default(),
+            None => {
+                let matches: Vec<Span> = regex.find_iter(line)
+                    .map(|match_obj| Span { start: match_obj.start(), end: match_obj.end() })
+                    .collect();
+
+                stats.matches += matches.len();
+
+                LineMatches {
+                    touched: !matches.is_empty(),
+                    matches,
+                    replacements: Vec::new(),
+                    continues_match: false,
+                }
+            }
+        };
+
+        if line_matches.touched != options.invert_match {
+            stats.matched_lines += 1;
+
+            if !options.multiline {
+                if let Some(template) = &options.replace {
+                    line_matches.replacements = expand_replacements(regex, line, template);
+                }
+            }
+
+            let mut sink_line = SinkLine {
+                line_number: index + 1,
+                byte_offset: record.byte_offset,
+                text: line,
+                terminator: record.terminator,
+                matches: line_matches.matches,
+                replacements: line_matches.replacements,
+                continues_match: line_matches.continues_match,
+                patterns: Vec::new(),
+                match_patterns: Vec::new(),
+            };
+
+            if let Some(pattern_set) = pattern_set {
+                pattern_set.annotate(options, source, &mut sink_line, continued_pattern);
+
+                if let Some(pattern) = sink_line.match_patterns.last() {
+                    continued_pattern = *pattern;
+                }
+            }
+
+            sink.matched_line(options, source, &sink_line);
+        }
+    }
+
+    sink.end_source(options, source, &stats);
+
+    stats
+}
+
+/// Matches the pattern against all of `data` and splits each match into spans
+/// for the `records` of data it touches. A replacement for a match is given to
+/// its first span, later spans of the same match are replaced with nothing.
+/// Returns the matches for each line and the total number of matches.
+fn search_multiline(
+    regex: &Regex,
+    options: &Options,
+    data: &str,
+    records: &[Record]
+) -> (Vec<LineMatches>, usize) {
+    let mut line_matches: Vec<LineMatches> = vec![LineMatches::default(); records.len()];
+
+    // byte offsets of the start and end of each record, excluding terminators
+    let bounds: Vec<(usize, usize)> = records.iter()
+        .map(|record| (record.byte_offset, record.byte_offset + record.text.len()))
+        .collect();
+
+    let mut num_matches = 0;
+    let mut first_line = 0;
+
+    for captures in regex.captures_iter(data) {
+        let match_obj = captures.get(0).expect("group 0 always participates");
+        let (start, end) = (match_obj.start(), match_obj.end());
+        num_matches += 1;
+
+        let mut replacement = options.replace.as_ref().map(|template| {
+            let mut replacement = String::new();
+            captures.expand(template, &mut replacement);
+            replacement
+        });
+
+        while first_line < bounds.len() && bounds[first_line].1 < start {
+            first_line += 1;
+        }
+
+        for (index, (line_start, line_end)) in bounds.iter().enumerate().skip(first_line) {
+            let touches_line = end > *line_start || (start == end && start >= *line_start);
+            if !touches_line {
+                break;
+            }
+
+            line_matches[index].touched = true;
+
+            let span_start = usize::max(start, *line_start) - line_start;
+            let span_end = usize::min(end, *line_end) - line_start;
+
+            // skip spans that only cover a line terminator
+            if span_start == span_end && start != end {
+                continue;
+            }
+
+            if start < *line_start && line_matches[index].matches.is_empty() {
+                line_matches[index].continues_match = true;
+            }
+
+            line_matches[index].matches.push(Span { start: span_start, end: span_end });
+
+            if options.replace.is_some() {
+                line_matches[index].replacements.push(replacement.take().unwrap_or_default());
+            }
+        }
+    }
+
+    (line_matches, num_matches)
+}
+
+/// Splits data of a source into lines terminated by
+/// [line_terminator](Options::line_terminator), or into records when a
+/// [record separator](Options::record_separator) is set. Empty records are skipped.
+/// A `\r` before a newline is part of the terminator when [uses_crlf] is `true`.
+pub fn split_records<'a>(data: &'a str, options: &Options) -> Vec<Record<'a>> {
+    let mut records: Vec<Record> = Vec::new();
+
+    let separator = match &options.record_separator {
+        Some(separator) => &separator.0,
+        None => {
+            let crlf = uses_crlf(data, options);
+            let mut byte_offset = 0;
+            let terminator_len = options.line_terminator().len();
+
+            for line in data.split_terminator(options.line_terminator()) {
+                let line_end = byte_offset + line.len();
+                let terminator_end = usize::min(line_end + terminator_len, data.len());
+
+                let text = if crlf && line.ends_with('\r') && terminator_end > line_end {
+                    &line[..line.len() - 1]
+                } else {
+                    line
+                };
+
+                records.push(Record {
+                    byte_offset,
+                    text,
+                    terminator: &data[byte_offset + text.len()..terminator_end],
+                });
+
+                byte_offset += line.len() + terminator_len;
+            }
+
+            return records;
+        }
+    };
+
+    let mut previous = 0;
+    for captures in separator.captures_iter(data) {
+        // Empty separator matches such as `\b` would split at every position.
+        if captures.get(0).is_some_and(|separator| separator.is_empty()) {
+            continue;
+        }
+
+        let consumed = captures.get(1)
+            .or_else(|| captures.get(0))
+            .expect("group 0 always participates");
+
+        if consumed.start() > previous {
+            records.push(Record {
+                byte_offset: previous,
+                text: &data[previous..consumed.start()],
+                terminator: consumed.as_str(),
+            });
+        }
+
+        previous = usize::max(previous, consumed.end());
+    }
+
+    if previous < data.len() {
+        records.push(Record {
+            byte_offset: previous,
+            text: &data[previous..],
+            terminator: "",
+        });
+    }
+
+    records
+}
+
+/// Checks if lines of `data` are terminated by `\r\n`. Uses the `crlf` option when it
+/// is set, otherwise checks the terminator of the first line. Always `false` when
+/// lines are terminated by NUL bytes.
+pub fn uses_crlf(data: &str, options: &Options) -> bool {
+    if options.null_data {
+        return false;
+    }
+
+    match options.crlf {
+        Some(crlf) => crlf,
+        None => data.find('\n').is_some_and(|index| data[..index].ends_with('\r')),
+    }
+}
+
+/// Expands `template` for every match inside of `line`. Capture groups can be
+/// referenced with `$1`, `$name` or `${name}`, `$$` is a literal `$`.
+/// Returns one replacement per match.
+pub fn expand_replacements(regex: &Regex, line: &str, template: &str) -> Vec<String> {
+    regex.captures_iter(line)
+        .map(|captures| {
+            let mut replacement = String::new();
+            captures.expand(template, &mut replacement);
+            replacement
+        })
+        .collect()
+}
+
 /// Searches data of a source line by line, returns matches.
-pub fn search_lines<'a>(regex: &'a Regex, data: &'a String) -> Vec<Matches<'a, 'a>> {
+pub fn search_lines<'a>(regex: &'a Regex, data: &'a str) -> Vec<Matches<'a, 'a>> {
     let mut matches: Vec<Matches> = Vec::new();
 
-    let lines = data.split("\n");
+    let lines = data.split('\n');
     for line in lines {
-        matches.push(regex.find_iter(&line));
+        matches.push(regex.find_iter(line));
     }
 
     matches
 }
 
 /// Searches data of source and returns the number of matches found.
-pub fn count_matching_lines(regex: &Regex, data: &String, invert_match: bool) -> usize {
+pub fn count_matching_lines(regex: &Regex, data: &str, invert_match: bool) -> usize {
     let mut matching_lines: usize = 0;
 
-    let lines = data.split("\n");
+    let lines = data.split('\n');
     for line in lines {
-        let mut match_iter = regex.find_iter(&line);
+        let mut match_iter = regex.find_iter(line);
         let line_has_match = match_iter.next().is_some();
 
-        if line_has_match && !invert_match {
-            matching_lines += 1;
-        }
-        else if !line_has_match && invert_match {
+        if line_has_match != invert_match {
             matching_lines += 1;
         }
     }
@@ -53,21 +446,24 @@ pub fn count_matching_lines(regex: &Regex, data: &String, invert_match: bool) ->
 
 /// Combines patterns into a single regex
 fn build_pattern_string(options: &Options) -> String {
-    let mut patterns = if options.line_match {
+    build_patterns(options).join("|")
+}
+
+/// Applies line or word matching to each pattern.
+fn build_patterns(options: &Options) -> Vec<String> {
+    let patterns = if options.line_match {
         apply_line_matching(&options.patterns)
     }
     else {
         options.patterns.clone()
     };
 
-    patterns = if options.word_match && !options.line_match {
+    if options.word_match && !options.line_match {
         apply_word_matching(&patterns)
     }
     else {
         patterns
-    };
-
-    patterns.join("|")
+    }
 }
 
 /// Combines flags for regex.
@@ -78,25 +474,38 @@ fn build_flags(options: &Options) -> String {
         flags.push_str("(?i)");
     }
 
+    if options.multiline {
+        flags.push_str("(?m)");
+
+        // CRLF mode, `^` and `$` also match next to `\r`
+        if options.crlf != Some(false) && !options.null_data {
+            flags.push_str("(?R)");
+        }
+
+        if options.multiline_dotall {
+            flags.push_str("(?s)");
+        }
+    }
+
     flags
 }
 
 /// Maps patterns to new patterns that only match entire lines.
-fn apply_line_matching(patterns: &Vec<String>) -> Vec<String> {
+fn apply_line_matching(patterns: &[String]) -> Vec<String> {
     patterns
-        .into_iter()
+        .iter()
         .map(|pattern| {
-            String::from("^(") + &pattern + ")$"
+            String::from("^(") + pattern + ")$"
         })
         .collect::<Vec<String>>()
 }
 
 /// Maps patterns to patterns that only match whole words.
-fn apply_word_matching(patterns: &Vec<String>) -> Vec<String> {
+fn apply_word_matching(patterns: &[String]) -> Vec<String> {
     patterns
-        .into_iter()
+        .iter()
         .map(|pattern| {
-            String::from(r"\b") + &pattern + r"\b"
+            String::from(r"\b") + pattern + r"\b"
         })
         .collect::<Vec<String>>()
 }
\ No newline at end of file
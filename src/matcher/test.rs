@@ -1,18 +1,37 @@
+#![cfg(test)]
 #![allow(unused_imports)]
 use super::*;
 
+use crate::sink::EventCollector;
+
 #[test]
 fn ignore_case_flag() {
-    let mut options = Options::default();
-    options.ignore_case = true;
+    let options = Options {
+        ignore_case: true,
+        ..Default::default()
+    };
     let regex_flags = build_flags(&options);
     assert_eq!(regex_flags, String::from("(?i)"));
 }
 
+#[test]
+fn multiline_flags() {
+    let mut options = Options {
+        multiline_dotall: true,
+        ..Default::default()
+    };
+    assert_eq!(build_flags(&options), String::new());
+
+    options.multiline = true;
+    assert_eq!(build_flags(&options), String::from("(?m)(?R)(?s)"));
+
+    options.multiline_dotall = false;
+    options.crlf = Some(false);
+    assert_eq!(build_flags(&options), String::from("(?m)"));
+}
+
 #[test]
 fn line_matching_patterns() {
-    let mut options = Options::default();
-    options.line_match = true;
     let patterns = vec!(String::from("[^z]"), String::from("Hello, World!"));
     let line_patterns = apply_line_matching(&patterns);
     assert_eq!(line_patterns, vec!(String::from("^([^z])$"), String::from("^(Hello, World!)$")));
@@ -20,8 +39,6 @@ fn line_matching_patterns() {
 
 #[test]
 fn word_matching_patterns() {
-    let mut options = Options::default();
-    options.word_match = true;
     let patterns = vec!(String::from("[a-zA-Z]"), String::from("\\d"));
     let word_patterns = apply_word_matching(&patterns);
     assert_eq!(word_patterns, vec!(String::from("\\b[a-zA-Z]\\b"), String::from("\\b\\d\\b")));
@@ -29,8 +46,10 @@ fn word_matching_patterns() {
 
 #[test]
 fn build_pattern_string_from_options() {
-    let mut options = Options::default();
-    options.patterns = vec!(String::from("[xyz]"), String::from("orchestra"));
+    let mut options = Options {
+        patterns: vec!(String::from("[xyz]"), String::from("orchestra")),
+        ..Default::default()
+    };
     let pattern = build_pattern_string(&options);
     assert_eq!(pattern, String::from("[xyz]|orchestra"));
 
@@ -50,4 +69,227 @@ fn build_pattern_string_from_options() {
     options.word_match = true;
     let pattern = build_pattern_string(&options);
     assert_eq!(pattern, String::from("^([xyz])$|^(orchestra)$"));
-}
\ No newline at end of file
+}
+
+#[test]
+fn expand_replacement_templates() {
+    let regex = Regex::new(r"(?P<word>\w+)@(\d)").unwrap();
+    let line = "dew@1 and yet@2";
+
+    let replacements = expand_replacements(&regex, line, "$2-${word}");
+    assert_eq!(replacements, vec!(String::from("1-dew"), String::from("2-yet")));
+
+    let replacements = expand_replacements(&regex, line, "$$1");
+    assert_eq!(replacements, vec!(String::from("$1"), String::from("$1")));
+
+    let replacements = expand_replacements(&regex, line, "$missing");
+    assert_eq!(replacements, vec!(String::new(), String::new()));
+}
+
+#[test]
+fn multiline_matches_split_by_line() {
+    let options = Options {
+        multiline: true,
+        patterns: vec!(String::from(r"world of dew,\nis")),
+        replace: Some(String::from("X")),
+        ..Default::default()
+    };
+    let regex = build_regex(&options).unwrap();
+
+    let data = "This world of dew,\nis a world of dew,\nand yet, and yet.";
+    let records = split_records(data, &options);
+
+    let (line_matches, num_matches) = search_multiline(&regex, &options, data, &records);
+
+    assert_eq!(num_matches, 1);
+    assert_eq!(line_matches, vec!(
+        LineMatches {
+            touched: true,
+            matches: vec!(Span { start: 5, end: 18 }),
+            replacements: vec!(String::from("X")),
+            continues_match: false,
+        },
+        LineMatches {
+            touched: true,
+            matches: vec!(Span { start: 0, end: 2 }),
+            replacements: vec!(String::new()),
+            continues_match: true,
+        },
+        LineMatches::default(),
+    ));
+}
+
+#[test]
+fn multiline_match_ending_with_terminator() {
+    let options = Options {
+        multiline: true,
+        patterns: vec!(String::from(r"dew,\n")),
+        ..Default::default()
+    };
+    let regex = build_regex(&options).unwrap();
+
+    let data = "is a world of dew,\nand yet.";
+    let records = split_records(data, &options);
+
+    let (line_matches, _) = search_multiline(&regex, &options, data, &records);
+
+    assert_eq!(line_matches[0].matches, vec!(Span { start: 14, end: 18 }));
+    assert_eq!(line_matches[1], LineMatches::default());
+}
+
+#[test]
+fn split_data_into_lines() {
+    let options = Options::default();
+    let records = split_records("dew\n\nyet\n", &options);
+    assert_eq!(records, vec!(
+        Record { byte_offset: 0, text: "dew", terminator: "\n" },
+        Record { byte_offset: 4, text: "", terminator: "\n" },
+        Record { byte_offset: 5, text: "yet", terminator: "\n" },
+    ));
+
+    let records = split_records("dew\nyet", &options);
+    assert_eq!(records[1], Record { byte_offset: 4, text: "yet", terminator: "" });
+
+    assert!(split_records("", &options).is_empty());
+}
+
+#[test]
+fn split_data_into_paragraphs() {
+    let mut options = Options::default();
+    options.parse_option(String::from("--paragraph")).unwrap();
+
+    let records = split_records("\nThis world\nof dew,\n\n \nand yet\n", &options);
+    assert_eq!(records, vec!(
+        Record { byte_offset: 1, text: "This world\nof dew,", terminator: "\n\n \n" },
+        Record { byte_offset: 23, text: "and yet", terminator: "\n" },
+    ));
+}
+
+#[test]
+fn split_data_before_record_headers() {
+    let mut options = Options::default();
+    options.parse_option(String::from(r"--record-separator=(\n)\[\d+\]")).unwrap();
+
+    let records = split_records("[1] dew\nmore\n[2] yet", &options);
+    assert_eq!(records, vec!(
+        Record { byte_offset: 0, text: "[1] dew\nmore", terminator: "\n" },
+        Record { byte_offset: 13, text: "[2] yet", terminator: "" },
+    ));
+}
+
+#[test]
+fn split_data_ignores_empty_separator_matches() {
+    let mut options = Options::default();
+    options.parse_option(String::from(r"--record-separator=\b")).unwrap();
+
+    let records = split_records("dew yet", &options);
+    assert_eq!(records, vec!(
+        Record { byte_offset: 0, text: "dew yet", terminator: "" },
+    ));
+}
+
+#[test]
+fn split_crlf_lines() {
+    let mut options = Options::default();
+    let records = split_records("dew\r\nyet\r\nend\r", &options);
+    assert_eq!(records, vec!(
+        Record { byte_offset: 0, text: "dew", terminator: "\r\n" },
+        Record { byte_offset: 5, text: "yet", terminator: "\r\n" },
+        Record { byte_offset: 10, text: "end\r", terminator: "" },
+    ));
+
+    options.crlf = Some(false);
+    let records = split_records("dew\r\nyet", &options);
+    assert_eq!(records[0], Record { byte_offset: 0, text: "dew\r", terminator: "\n" });
+
+    options.crlf = Some(true);
+    let records = split_records("dew\nyet\r\n", &options);
+    assert_eq!(records, vec!(
+        Record { byte_offset: 0, text: "dew", terminator: "\n" },
+        Record { byte_offset: 4, text: "yet", terminator: "\r\n" },
+    ));
+}
+
+#[test]
+fn detect_crlf_line_endings() {
+    let mut options = Options::default();
+    assert!(uses_crlf("dew\r\nyet\n", &options));
+    assert!(!uses_crlf("dew\nyet\r\n", &options));
+    assert!(!uses_crlf("dew\r", &options));
+
+    options.crlf = Some(true);
+    assert!(uses_crlf("dew\n", &options));
+
+    options.null_data = true;
+    assert!(!uses_crlf("dew\r\n", &options));
+}
+
+#[test]
+fn finds_matching_patterns() {
+    let options = Options {
+        patterns: vec!(String::from("dew"), String::from("world"), String::from("wor")),
+        ..Default::default()
+    };
+
+    let regex = build_regex(&options).unwrap();
+    let regexes = build_pattern_regexes(&options).unwrap();
+    assert_eq!(regexes.len(), 3);
+
+    let mut source = Source::new(String::from("res/test/haiku.txt"));
+    source.read_data().unwrap();
+
+    let line = SinkLine {
+        line_number: 1,
+        byte_offset: 0,
+        text: "This world of dew,",
+        terminator: "\n",
+        matches: regex.find_iter("This world of dew,")
+            .map(|match_obj| Span { start: match_obj.start(), end: match_obj.end() })
+            .collect(),
+        replacements: Vec::new(),
+        continues_match: false,
+        patterns: Vec::new(),
+        match_patterns: Vec::new(),
+    };
+
+    let indices: Vec<Option<usize>> = line.matches.iter()
+        .map(|span| matching_pattern(&regexes, &options, &source, &line, span))
+        .collect();
+
+    assert_eq!(indices, vec!(Some(1), Some(0)));
+}
+
+#[test]
+fn pattern_set_annotates_lines() {
+    let mut source = Source::new(String::from("res/test/haiku.txt"));
+    source.read_data().unwrap();
+
+    let mut options = Options {
+        patterns: vec!(String::from("dew"), String::from("world"), String::from("wor")),
+        ..Default::default()
+    };
+    let regex = build_regex(&options).unwrap();
+    let pattern_set = PatternSet::new(&options).unwrap();
+
+    let mut collector = EventCollector::default();
+    search_source_with_patterns(&regex, Some(&pattern_set), &options, &source, &mut collector);
+
+    assert_eq!(collector.line_patterns, vec!(
+        (vec!(0, 1, 2), vec!(Some(1), Some(0))),
+        (vec!(0, 1, 2), vec!(Some(1), Some(0))),
+    ));
+
+    options.patterns = vec!(String::from(r"dew,\nis"), String::from("yet"));
+    options.multiline = true;
+    let regex = build_regex(&options).unwrap();
+    let pattern_set = PatternSet::new(&options).unwrap();
+
+    let mut collector = EventCollector::default();
+    search_source_with_patterns(&regex, Some(&pattern_set), &options, &source, &mut collector);
+
+    assert_eq!(collector.line_patterns, vec!(
+        (vec!(0), vec!(Some(0))),
+        (vec!(0), vec!(Some(0))),
+        (vec!(1), vec!(Some(1), Some(1))),
+    ));
+}
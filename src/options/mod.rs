@@ -2,12 +2,16 @@ mod test;
 
 use crate::source;
 use crate::colors;
+use crate::hyperlink;
+use crate::template;
 
+use regex::Regex;
 use source::Source;
 use std::error::Error;
 use std::env::var_os;
-use std::io::{stderr, Write};
+use std::io::{stderr, stdout, IsTerminal, Write};
 use colors::Colors;
+use template::Template;
 
 /// #### Options for a run of GrepRS.
 ///
@@ -17,8 +21,8 @@ use colors::Colors;
 pub struct Options {
     /// Patterns to find matches for.
     pub patterns: Vec<String>,
-    /// If output should use color. Defaults to `true` if the current environment indicates
-    /// color output support.
+    /// If output should use color. Defaults to `true` if stdout is a terminal and the
+    /// current environment doesn't disable colors, see [supports_color](Options::supports_color).
     pub color_output: bool,
     /// Contains the colors that should be used for highlighting different aspects of output.
     /// See the colors module for more information.
@@ -40,10 +44,171 @@ pub struct Options {
     /// If `true`, don't write any error messages. Defaults to `false`.
     pub no_messages: bool,
     /// If `true`, counts lines with matches, respects invert_match. Defaults to `false`.
+    /// See `count_matches`, `total` and `include_zero` for what is counted and written.
     pub count_lines: bool,
+    /// Format used for writing results. Defaults to [OutputFormat::Text].
+    pub output_format: OutputFormat,
+    /// If `true`, file name prefixes are followed by a NUL byte instead of a separator.
+    /// Defaults to `false`.
+    pub null_after_name: bool,
+    /// If `true`, input and output lines are terminated by NUL bytes instead of newlines.
+    /// Defaults to `false`.
+    pub null_data: bool,
+    /// If `true`, only the matching parts of selected lines are written, each on a separate line.
+    /// Defaults to `false`.
+    pub only_matching: bool,
+    /// Template used to replace matches in output. Capture groups can be referenced with
+    /// `$1` or `${name}`. Sources are only modified when `in_place` is set. Defaults to `None`.
+    pub replace: Option<String>,
+    /// If set, sources are rewritten with replacements applied. A backup of each source
+    /// is kept with the contained suffix when it is not empty. Defaults to `None`.
+    pub in_place: Option<String>,
+    /// If `true`, a unified diff of the changes replacements would make is written instead
+    /// of search results, sources are not modified. Defaults to `false`.
+    pub diff: bool,
+    /// If `true`, patterns are matched against entire sources so matches can span
+    /// multiple lines. Every line touched by a match is selected. Defaults to `false`.
+    pub multiline: bool,
+    /// If `true`, `.` also matches line terminators. Only has an effect with `multiline`.
+    /// Defaults to `false`.
+    pub multiline_dotall: bool,
+    /// If set, sources are split into records at matches of the separator instead of into lines.
+    /// Records are selected, counted and written as a whole. Defaults to `None`.
+    pub record_separator: Option<RecordSeparator>,
+    /// If `\r\n` should be treated as a line terminator. `None` detects CRLF line endings
+    /// from the first line of each source. Output keeps the original line endings.
+    /// Defaults to `None`.
+    pub crlf: Option<bool>,
+    /// If `true`, diagnostic messages, e.g. about malformed colors, are written to stderr.
+    /// Defaults to `false`.
+    pub debug: bool,
+    /// Format of OSC 8 hyperlinks that wrap file name prefixes, only used when output is
+    /// colored. See [parse_format](crate::hyperlink::parse_format). Defaults to `None`.
+    pub hyperlink_format: Option<String>,
+    /// If `true`, lines are grouped by source under a heading with the file name instead of
+    /// being prefixed with it, and are prefixed with their line number. Defaults to `false`.
+    pub heading: bool,
+    /// If `true`, prefixes of lines are followed by a tab so content is aligned, and line
+    /// numbers are padded to a minimum width. Defaults to `false`.
+    pub initial_tab: bool,
+    /// Separator written after prefix fields of selected lines. Defaults to `:`.
+    pub field_match_separator: String,
+    /// Separator written after prefix fields of context lines. Defaults to `-`.
+    pub field_context_separator: String,
+    /// If `true`, `path:line:column:text` is written once per match for use with
+    /// Vim's quickfix list. Colors, headings and separators are ignored. Defaults to `false`.
+    pub vimgrep: bool,
+    /// Path of a rules file. If set, sources are linted with the rules instead of searched
+    /// for patterns, see [Linter](crate::lint::Linter). Defaults to `None`.
+    pub rules: Option<String>,
+    /// Path of a baseline file. If set, matches recorded in the baseline are not reported
+    /// and the number of new and fixed matches is written to stderr. Defaults to `None`.
+    pub baseline: Option<String>,
+    /// Path a baseline of every match is written to, see [BaselineFilter](crate::baseline::BaselineFilter).
+    /// Defaults to `None`.
+    pub write_baseline: Option<String>,
+    /// If set, named capture groups of matches are written as rows of a table in the
+    /// contained format instead of search results, see [Extractor](crate::extract::Extractor).
+    /// Defaults to `None`.
+    pub extract: Option<ExtractFormat>,
+    /// Columns written before capture groups when extracting. Defaults to no columns.
+    pub extract_columns: Vec<ExtractColumn>,
+    /// If set, a table of how often each distinct match occurs is written instead of search
+    /// results. Counts the capture group with the contained number or name, or entire matches
+    /// when it is empty, see [Tally](crate::tally::Tally). Defaults to `None`.
+    pub tally: Option<String>,
+    /// Maximum number of rows of a tally. Defaults to `None`.
+    pub top: Option<usize>,
+    /// If `true`, counts are the number of matches instead of selected lines. Implies
+    /// `count_lines`. Defaults to `false`.
+    pub count_matches: bool,
+    /// If `true`, a single count summed over all sources is written instead of a count per
+    /// source. Implies `count_lines`. Defaults to `false`.
+    pub total: bool,
+    /// If `true`, counts are written for sources without any selected lines or matches.
+    /// Defaults to `true`.
+    pub include_zero: bool,
+    /// If `true`, statistics about the entire run are written after the results.
+    /// Defaults to `false`.
+    pub stats: bool,
+    /// Annotates each selected line with the patterns that match it, and each
+    /// match of `only_matching` with the pattern that produced it. Defaults to `None`.
+    pub show_pattern: Option<PatternLabel>,
+    /// ANSI codes cycled through for the matches of each pattern, see
+    /// [pattern_color](Options::pattern_color). Defaults to empty.
+    pub pattern_palette: Vec<String>,
+    /// ANSI codes of individual patterns, indexed like `patterns`. Take
+    /// precedence over `pattern_palette`. Defaults to empty.
+    pub pattern_colors: Vec<Option<String>>,
+    /// Labels of individual patterns, indexed like `patterns`, see
+    /// [pattern_label](Options::pattern_label). Defaults to empty.
+    pub pattern_labels: Vec<Option<String>>,
 }
 
-impl Options {
+/// #### Pattern that separates records of a source.
+///
+/// Text matched by the pattern is removed from records. When the pattern contains a
+/// capture group only the text matched by the first group is removed, e.g.
+/// `(\n)\d{4}-\d{2}-\d{2}` splits records before lines starting with a date while
+/// keeping the date in the following record.
+#[derive(Debug, Clone)]
+pub struct RecordSeparator(pub Regex);
+
+impl RecordSeparator {
+    /// Pattern used by `--paragraph`, splits records at blank lines. Leading blank
+    /// lines and the newline at the end of a source are also treated as separators.
+    pub const PARAGRAPH: &'static str = r"\n(?:[ \t]*\n)+|\n\z|\A(?:[ \t]*\n)+";
+}
+
+impl PartialEq for RecordSeparator {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.as_str() == other.0.as_str()
+    }
+}
+
+impl Eq for RecordSeparator {}
+
+/// #### Formats results can be written in.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub enum OutputFormat {
+    /// Human readable text, see [OutputBuffer](crate::output::OutputBuffer).
+    Text,
+    /// JSON Lines with one object per event, see [JsonPrinter](crate::json::JsonPrinter).
+    Json,
+    /// A SARIF 2.1.0 log with one result per match, see [SarifPrinter](crate::sarif::SarifPrinter).
+    Sarif,
+    /// A custom template rendered for every match, see [TemplatePrinter](crate::template::TemplatePrinter).
+    Template(Template),
+}
+
+/// #### Formats capture groups can be extracted as.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ExtractFormat {
+    /// Comma separated values.
+    Csv,
+    /// Tab separated values.
+    Tsv,
+}
+
+/// #### Columns that can be extracted in addition to capture groups.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ExtractColumn {
+    /// Path of the source of a match.
+    Path,
+    /// Line number of a match.
+    Line,
+}
+
+/// #### Labels patterns are shown with, see [Options::show_pattern].
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum PatternLabel {
+    /// Number of the pattern in the order patterns were given, starting from 1.
+    Index,
+    /// The label of the pattern set with `--label-for`, otherwise the pattern as it was given.
+    Pattern,
+}
+
+impl Default for Options {
     /// Returns default Options.
     /// ```text
     /// Options {
@@ -58,9 +223,42 @@ impl Options {
     ///     silent: false,
     ///     no_messages: false,
     ///     count_lines: false,
+    ///     output_format: OutputFormat::Text,
+    ///     null_after_name: false,
+    ///     null_data: false,
+    ///     only_matching: false,
+    ///     replace: None,
+    ///     in_place: None,
+    ///     diff: false,
+    ///     multiline: false,
+    ///     multiline_dotall: false,
+    ///     record_separator: None,
+    ///     crlf: None,
+    ///     debug: false,
+    ///     hyperlink_format: None,
+    ///     heading: false,
+    ///     initial_tab: false,
+    ///     field_match_separator: String::from(":"),
+    ///     field_context_separator: String::from("-"),
+    ///     vimgrep: false,
+    ///     rules: None,
+    ///     baseline: None,
+    ///     write_baseline: None,
+    ///     extract: None,
+    ///     extract_columns: Vec::new(),
+    ///     tally: None,
+    ///     top: None,
+    ///     count_matches: false,
+    ///     total: false,
+    ///     include_zero: true,
+    ///     stats: false,
+    ///     show_pattern: None,
+    ///     pattern_palette: Vec::new(),
+    ///     pattern_colors: Vec::new(),
+    ///     pattern_labels: Vec::new(),
     /// }
     /// ```
-    pub fn default() -> Options {
+    fn default() -> Options {
         Options {
             patterns: Vec::new(),
             color_output: Self::supports_color(),
@@ -73,9 +271,44 @@ impl Options {
             silent: false,
             no_messages: false,
             count_lines: false,
+            output_format: OutputFormat::Text,
+            null_after_name: false,
+            null_data: false,
+            only_matching: false,
+            replace: None,
+            in_place: None,
+            diff: false,
+            multiline: false,
+            multiline_dotall: false,
+            record_separator: None,
+            crlf: None,
+            debug: false,
+            hyperlink_format: None,
+            heading: false,
+            initial_tab: false,
+            field_match_separator: String::from(":"),
+            field_context_separator: String::from("-"),
+            vimgrep: false,
+            rules: None,
+            baseline: None,
+            write_baseline: None,
+            extract: None,
+            extract_columns: Vec::new(),
+            tally: None,
+            top: None,
+            count_matches: false,
+            total: false,
+            include_zero: true,
+            stats: false,
+            show_pattern: None,
+            pattern_palette: Vec::new(),
+            pattern_colors: Vec::new(),
+            pattern_labels: Vec::new(),
         }
     }
+}
 
+impl Options {
     /// Parses option argument and applies option to current options struct.
     pub fn parse_option(&mut self, arg: String) -> Result<(), Box<dyn Error>> {
         let split_arg = Self::split_option(&arg);
@@ -115,8 +348,128 @@ impl Options {
         else if option == "-c" || option == "--count" {
             self.count_lines = true;
         }
+        else if option == "--count-matches" {
+            self.count_lines = true;
+            self.count_matches = true;
+        }
+        else if option == "--total" {
+            self.count_lines = true;
+            self.total = true;
+        }
+        else if option == "--include-zero" || option == "--hide-zero" {
+            self.include_zero = option == "--include-zero";
+        }
+        else if option == "--stats" {
+            self.stats = true;
+        }
+        else if option == "--show-pattern" {
+            self.show_pattern = match value {
+                "" | "index" => Some(PatternLabel::Index),
+                "pattern" => Some(PatternLabel::Pattern),
+                _ => return Err(Box::from(format!("Unknown pattern label: {}", value))),
+            };
+        }
+        else if option == "--pattern-colors" {
+            self.handle_pattern_colors(value)?;
+        }
+        else if option == "--color-for" {
+            self.handle_color_for(value)?;
+        }
+        else if option == "--label-for" {
+            self.handle_label_for(value)?;
+        }
         else if option == "--color" || option == "--colour" {
-            self.handle_color(value);
+            self.handle_color(value)?;
+        }
+        else if option == "--json" {
+            self.output_format = OutputFormat::Json;
+        }
+        else if option == "--format" {
+            self.handle_format(value)?;
+        }
+        else if option == "-Z" || option == "--null" {
+            self.null_after_name = true;
+        }
+        else if option == "-z" || option == "--null-data" {
+            self.null_data = true;
+        }
+        else if option == "-o" || option == "--only-matching" {
+            self.only_matching = true;
+        }
+        else if option == "--replace" {
+            self.replace = Some(String::from(value));
+        }
+        else if option == "--in-place" {
+            self.in_place = Some(String::from(value));
+        }
+        else if option == "--diff" {
+            self.diff = true;
+        }
+        else if option == "-U" || option == "--multiline" {
+            self.multiline = true;
+        }
+        else if option == "--multiline-dotall" {
+            self.multiline_dotall = true;
+        }
+        else if option == "--record-separator" {
+            self.handle_record_separator(value)?;
+        }
+        else if option == "--paragraph" {
+            self.record_separator = Some(RecordSeparator(Regex::new(RecordSeparator::PARAGRAPH)?));
+        }
+        else if option == "--crlf" || option == "--no-crlf" {
+            self.crlf = Some(option == "--crlf");
+        }
+        else if option == "--debug" {
+            self.debug = true;
+        }
+        else if option == "--colors-theme" {
+            self.handle_colors_theme(value)?;
+        }
+        else if option == "--heading" || option == "--no-heading" {
+            self.heading = option == "--heading";
+        }
+        else if option == "-T" || option == "--initial-tab" {
+            self.initial_tab = true;
+        }
+        else if option == "--field-match-separator" {
+            self.field_match_separator = String::from(value);
+        }
+        else if option == "--field-context-separator" {
+            self.field_context_separator = String::from(value);
+        }
+        else if option == "--vimgrep" {
+            self.vimgrep = true;
+        }
+        else if option == "--rules" {
+            self.rules = Some(String::from(value));
+        }
+        else if option == "--baseline" {
+            self.baseline = Some(String::from(value));
+        }
+        else if option == "--write-baseline" {
+            self.write_baseline = Some(String::from(value));
+        }
+        else if option == "--extract" {
+            self.extract = match value {
+                "csv" => Some(ExtractFormat::Csv),
+                "tsv" => Some(ExtractFormat::Tsv),
+                _ => return Err(Box::from(format!("Unknown extract format: {}", value))),
+            };
+        }
+        else if option == "--extract-columns" {
+            self.handle_extract_columns(value)?;
+        }
+        else if option == "--tally" {
+            self.tally = Some(String::from(value));
+        }
+        else if option == "--top" {
+            let top = value.parse()
+                .map_err(|_| format!("Invalid number of rows for --top: {}", value))?;
+            self.top = Some(top);
+        }
+        else if option == "--hyperlink-format" {
+            self.hyperlink_format = hyperlink::parse_format(value)?;
         }
         else {
             panic!("Invalid option: {}", option);
@@ -146,15 +499,15 @@ impl Options {
         if let Err(msg) = pattern_source.read_data(){
             writeln!(
                 &mut stderr(),
-                "{} {}",
-                "grepRS:",
+                "grepRS: {}",
                 msg
             ).expect("Could not write to stderr.");
         };
 
         let patterns = pattern_source.data;
 
-        for pattern in patterns.split("\n") {
+        // a trailing newline ends the last pattern instead of adding an empty one
+        for pattern in patterns.split_terminator("\n") {
             self.patterns.push(String::from(pattern));
         }
     }
@@ -186,32 +539,217 @@ impl Options {
         }
     }
 
-    fn handle_color(&mut self, value: &str) {
+    /// Sets `color_output` from the value of a `--color` option. An empty value
+    /// is the same as `auto`, which only colors output when supported.
+    fn handle_color(&mut self, value: &str) -> Result<(), Box<dyn Error>> {
         match value {
-            "always" => self.color_output = true,
-            "never" => self.color_output = false,
-            _ => {},
+            "always" | "yes" | "force" => self.color_output = true,
+            "never" | "no" | "none" => self.color_output = false,
+            "auto" | "tty" | "if-tty" | "" => self.color_output = Self::supports_color(),
+            _ => return Err(Box::from(format!("Invalid color value: {}", value))),
         }
+
+        Ok(())
     }
 
-    /// Check if running in a environment that supports color output.
-    // TODO: Add support for more environments.
-    fn supports_color() -> bool {
-        match var_os("COLORTERM") {
-            Some(_) => {
-                true
+    /// Sets `output_format` from the value of a `--format` option. Values
+    /// other than the name of a format are parsed as a [Template], which must
+    /// contain at least one placeholder.
+    fn handle_format(&mut self, value: &str) -> Result<(), Box<dyn Error>> {
+        self.output_format = match value {
+            "text" => OutputFormat::Text,
+            "json" => OutputFormat::Json,
+            "sarif" => OutputFormat::Sarif,
+            _ => {
+                let template = Template::parse(value)?;
+
+                // a value without placeholders is most likely a misspelled format
+                if !template.has_placeholders() {
+                    return Err(Box::from(format!(
+                        "Unknown output format: {}, expected text, json, sarif or a template with placeholders",
+                        value
+                    )));
+                }
+
+                OutputFormat::Template(template)
             },
-            None => {
-                false
+        };
+
+        Ok(())
+    }
+
+    /// Sets `extract_columns` from a comma separated list of `path` and `line`.
+    fn handle_extract_columns(&mut self, value: &str) -> Result<(), Box<dyn Error>> {
+        let mut columns = Vec::new();
+
+        for column in value.split(',').filter(|column| !column.is_empty()) {
+            columns.push(match column {
+                "path" => ExtractColumn::Path,
+                "line" => ExtractColumn::Line,
+                _ => return Err(Box::from(format!("Unknown extract column: {}", column))),
+            });
+        }
+
+        self.extract_columns = columns;
+
+        Ok(())
+    }
+
+    /// Sets `pattern_palette` from a colon separated list of color specs, or to
+    /// [PATTERN_PALETTE](colors::PATTERN_PALETTE) when no list is given.
+    /// See [parse_color_spec](Colors::parse_color_spec) for the accepted specs.
+    fn handle_pattern_colors(&mut self, value: &str) -> Result<(), Box<dyn Error>> {
+        if value.is_empty() {
+            self.pattern_palette = colors::PATTERN_PALETTE.iter().map(|code| String::from(*code)).collect();
+            return Ok(());
+        }
+
+        let mut palette = Vec::new();
+        for spec in value.split(':') {
+            match Colors::parse_color_spec(spec) {
+                Some(code) => palette.push(code),
+                None => return Err(Box::from(format!("Invalid pattern color: {}", spec))),
             }
         }
+
+        self.pattern_palette = palette;
+
+        Ok(())
+    }
+
+    /// Sets the color of the most recently given pattern.
+    fn handle_color_for(&mut self, spec: &str) -> Result<(), Box<dyn Error>> {
+        if self.patterns.is_empty() {
+            return Err(Box::from("--color-for requires a preceding -e pattern!"));
+        }
+
+        let code = Colors::parse_color_spec(spec)
+            .ok_or_else(|| format!("Invalid pattern color: {}", spec))?;
+
+        self.pattern_colors.resize(self.patterns.len(), None);
+        self.pattern_colors[self.patterns.len() - 1] = Some(code);
+
+        Ok(())
+    }
+
+    /// Sets the record separator, rejecting patterns that match the empty string
+    /// since they would split every source into single characters.
+    fn handle_record_separator(&mut self, value: &str) -> Result<(), Box<dyn Error>> {
+        let regex = Regex::new(value)?;
+        if regex.is_match("") {
+            return Err(Box::from(format!("Record separator matches the empty string: {}", value)));
+        }
+
+        self.record_separator = Some(RecordSeparator(regex));
+        Ok(())
+    }
+
+    /// Sets the label of the most recently given pattern.
+    fn handle_label_for(&mut self, label: &str) -> Result<(), Box<dyn Error>> {
+        if self.patterns.is_empty() {
+            return Err(Box::from("--label-for requires a preceding -e pattern!"));
+        }
+
+        if label.is_empty() {
+            return Err(Box::from("--label-for requires a label!"));
+        }
+
+        self.pattern_labels.resize(self.patterns.len(), None);
+        self.pattern_labels[self.patterns.len() - 1] = Some(String::from(label));
+
+        Ok(())
+    }
+
+    /// Replaces `colors` with those of a built-in theme. Colors set in the
+    /// environment still take precedence over the theme.
+    fn handle_colors_theme(&mut self, name: &str) -> Result<(), Box<dyn Error>> {
+        match Colors::theme(name) {
+            Some(theme) => {
+                self.colors = Colors::get_themed_colors(theme);
+                Ok(())
+            },
+            None => Err(Box::from(format!(
+                "Unknown colors theme: {}, expected one of: {}",
+                name,
+                colors::THEMES.join(", ")
+            ))),
+        }
+    }
+
+    /// Check if colors should be used for output written to stdout.
+    /// See [detect_color](Options::detect_color) for the rules that are used.
+    pub fn supports_color() -> bool {
+        Self::detect_color(stdout().is_terminal(), |name| var_os(name))
+    }
+
+    /// Decides if output should be colored based on whether it is written to a
+    /// terminal and the environment variables returned by `var`:
+    /// *   `NO_COLOR` set to a non-empty value disables color.
+    /// *   `CLICOLOR_FORCE` set to a value other than `0` enables color, even when
+    ///     output is not a terminal.
+    /// *   Otherwise color is only used for terminals, unless `TERM` is `dumb`
+    ///     or `CLICOLOR` is `0`.
+    pub fn detect_color<F>(is_terminal: bool, var: F) -> bool
+    where
+        F: Fn(&str) -> Option<std::ffi::OsString>
+    {
+        let is_set = |name: &str| var(name).is_some_and(|value| !value.is_empty());
+        let is_zero = |name: &str| var(name).is_some_and(|value| value == "0");
+
+        if is_set("NO_COLOR") {
+            false
+        }
+        else if is_set("CLICOLOR_FORCE") && !is_zero("CLICOLOR_FORCE") {
+            true
+        }
+        else {
+            is_terminal && !is_zero("CLICOLOR") && var("TERM").is_none_or(|term| term != "dumb")
+        }
+    }
+
+    /// Returns the terminator of input and output lines.
+    pub fn line_terminator(&self) -> &'static str {
+        if self.null_data {
+            "\0"
+        } else {
+            "\n"
+        }
+    }
+
+    /// Returns the color of matches of the pattern at `index`, set with `--color-for`
+    /// or cycled through `pattern_palette`. Returns `None` when the pattern has no
+    /// color of its own and `selected_match` should be used.
+    pub fn pattern_color(&self, index: usize) -> Option<&str> {
+        if let Some(Some(color)) = self.pattern_colors.get(index) {
+            return Some(color);
+        }
+
+        if self.pattern_palette.is_empty() {
+            None
+        } else {
+            Some(&self.pattern_palette[index % self.pattern_palette.len()])
+        }
+    }
+
+    /// Returns the label of the pattern at `index` set with `--label-for`,
+    /// otherwise the pattern itself.
+    pub fn pattern_label(&self, index: usize) -> &str {
+        match self.pattern_labels.get(index) {
+            Some(Some(label)) => label,
+            _ => &self.patterns[index],
+        }
+    }
+
+    /// Checks if matches are colored by the pattern that produced them.
+    pub fn uses_pattern_colors(&self) -> bool {
+        !self.pattern_palette.is_empty() || self.pattern_colors.iter().any(Option::is_some)
     }
 
     /// Attempts to split an option argument that is associated with a
     /// and return the option and value. Otherwise, returns option
     /// argument and an empty value string.
-    fn split_option<'a>(arg: &'a String) -> (&'a str, &'a str) {
-        let split_arg: Option<(&str, &str)> = arg.split_once("=");
+    fn split_option(arg: &str) -> (&str, &str) {
+        let split_arg: Option<(&str, &str)> = arg.split_once('=');
         if let Some((option, value)) = split_arg {
             // checks for "=" delimited values
             (option, value)
@@ -220,7 +758,7 @@ impl Options {
             (&arg[0..2], &arg[2..])
         }
         else {
-            (arg.as_str(), "")
+            (arg, "")
         }
     }
 }
\ No newline at end of file
@@ -7,7 +7,7 @@ fn default() {
     let default_options = Options::default();
     let expected_options = Options {
         patterns: Vec::new(),
-        color_output: true, // rust.yml sets COLORTERM=truecolor, if testing locally make sure to set.
+        color_output: Options::supports_color(),
         colors: Colors::get_colors(),
         file_prefix: false,
         ignore_case: false,
@@ -17,6 +17,39 @@ fn default() {
         silent: false,
         no_messages: false,
         count_lines: false,
+        output_format: OutputFormat::Text,
+        null_after_name: false,
+        null_data: false,
+        only_matching: false,
+        replace: None,
+        in_place: None,
+        diff: false,
+        multiline: false,
+        multiline_dotall: false,
+        record_separator: None,
+        crlf: None,
+        debug: false,
+        hyperlink_format: None,
+        heading: false,
+        initial_tab: false,
+        field_match_separator: String::from(":"),
+        field_context_separator: String::from("-"),
+        vimgrep: false,
+        rules: None,
+        baseline: None,
+        write_baseline: None,
+        extract: None,
+        extract_columns: Vec::new(),
+        tally: None,
+        top: None,
+        count_matches: false,
+        total: false,
+        include_zero: true,
+        stats: false,
+        show_pattern: None,
+        pattern_palette: Vec::new(),
+        pattern_colors: Vec::new(),
+        pattern_labels: Vec::new(),
     };
     assert_eq!(default_options, expected_options);
 }
@@ -45,16 +78,20 @@ fn parse_explicit_patterns() {
 #[test]
 fn parse_no_filename() {
     let arg = String::from("-h");
-    let mut options = Options::default();
-    options.file_prefix = true;
+    let mut options = Options {
+        file_prefix: true,
+        ..Default::default()
+    };
     options.parse_option(arg);
-    assert_eq!(options.file_prefix, false);
+    assert!(!options.file_prefix);
 
     let arg = String::from("--no-filename");
-    let mut options = Options::default();
-    options.file_prefix = true;
+    let mut options = Options {
+        file_prefix: true,
+        ..Default::default()
+    };
     options.parse_option(arg);
-    assert_eq!(options.file_prefix, false);
+    assert!(!options.file_prefix);
 }
 
 #[test]
@@ -62,12 +99,12 @@ fn parse_with_filename() {
     let arg = String::from("-H");
     let mut options = Options::default();
     options.parse_option(arg);
-    assert_eq!(options.file_prefix, true);
+    assert!(options.file_prefix);
 
     let arg = String::from("--with-filename");
     let mut options = Options::default();
     options.parse_option(arg);
-    assert_eq!(options.file_prefix, true);
+    assert!(options.file_prefix);
 }
 
 #[test]
@@ -86,6 +123,11 @@ fn read_pattern_file() {
     let mut options = Options::default();
     options.parse_option(arg);
     assert_eq!(options.patterns, vec!(String::from("dew"), String::from("s")));
+
+    let arg = String::from("--file=res/test/terminated_patterns.txt");
+    let mut options = Options::default();
+    options.parse_option(arg);
+    assert_eq!(options.patterns, vec!(String::from("dew"), String::from("s")));
 }
 
 #[test]
@@ -93,26 +135,28 @@ fn parse_ignore_case() {
     let arg = String::from("-i");
     let mut options = Options::default();
     options.parse_option(arg);
-    assert_eq!(options.ignore_case, true);
+    assert!(options.ignore_case);
 
     let arg = String::from("-y");
     let mut options = Options::default();
     options.parse_option(arg);
-    assert_eq!(options.ignore_case, true);
+    assert!(options.ignore_case);
 
     let arg = String::from("--ignore-case");
     let mut options = Options::default();
     options.parse_option(arg);
-    assert_eq!(options.ignore_case, true);
+    assert!(options.ignore_case);
 }
 
 #[test]
 fn parse_no_ignore_case() {
     let arg = String::from("--no-ignore-case");
-    let mut options = Options::default();
-    options.ignore_case = true;
+    let mut options = Options {
+        ignore_case: true,
+        ..Default::default()
+    };
     options.parse_option(arg);
-    assert_eq!(options.ignore_case, false);
+    assert!(!options.ignore_case);
 }
 
 #[test]
@@ -120,12 +164,12 @@ fn parse_invert_match() {
     let arg = String::from("-v");
     let mut options = Options::default();
     options.parse_option(arg);
-    assert_eq!(options.invert_match, true);
+    assert!(options.invert_match);
 
     let arg = String::from("--invert-match");
     let mut options = Options::default();
     options.parse_option(arg);
-    assert_eq!(options.invert_match, true);
+    assert!(options.invert_match);
 }
 
 #[test]
@@ -133,12 +177,12 @@ fn parse_line_match() {
     let arg = String::from("-x");
     let mut options = Options::default();
     options.parse_option(arg);
-    assert_eq!(options.line_match, true);
+    assert!(options.line_match);
 
     let arg = String::from("--line-regexp");
     let mut options = Options::default();
     options.parse_option(arg);
-    assert_eq!(options.line_match, true);
+    assert!(options.line_match);
 }
 
 #[test]
@@ -146,12 +190,12 @@ fn parse_word_match() {
     let arg = String::from("-w");
     let mut options = Options::default();
     options.parse_option(arg);
-    assert_eq!(options.word_match, true);
+    assert!(options.word_match);
 
     let arg = String::from("--word-regexp");
     let mut options = Options::default();
     options.parse_option(arg);
-    assert_eq!(options.word_match, true);
+    assert!(options.word_match);
 }
 
 #[test]
@@ -159,20 +203,20 @@ fn parse_silent() {
     let arg = String::from("-q");
     let mut options = Options::default();
     options.parse_option(arg);
-    assert_eq!(options.silent, true);
-    assert_eq!(options.no_messages, true);
+    assert!(options.silent);
+    assert!(options.no_messages);
 
     let arg = String::from("--quiet");
     let mut options = Options::default();
     options.parse_option(arg);
-    assert_eq!(options.silent, true);
-    assert_eq!(options.no_messages, true);
+    assert!(options.silent);
+    assert!(options.no_messages);
 
     let arg = String::from("--silent");
     let mut options = Options::default();
     options.parse_option(arg);
-    assert_eq!(options.silent, true);
-    assert_eq!(options.no_messages, true);
+    assert!(options.silent);
+    assert!(options.no_messages);
 }
 
 #[test]
@@ -180,12 +224,12 @@ fn parse_no_messages() {
     let arg = String::from("-s");
     let mut options = Options::default();
     options.parse_option(arg);
-    assert_eq!(options.no_messages, true);
+    assert!(options.no_messages);
 
     let arg = String::from("--no-messages");
     let mut options = Options::default();
     options.parse_option(arg);
-    assert_eq!(options.no_messages, true);
+    assert!(options.no_messages);
 }
 
 #[test]
@@ -193,37 +237,457 @@ fn parse_count_lines() {
     let arg = String::from("-c");
     let mut options = Options::default();
     options.parse_option(arg);
-    assert_eq!(options.count_lines, true);
+    assert!(options.count_lines);
 
     let arg = String::from("--count");
     let mut options = Options::default();
     options.parse_option(arg);
-    assert_eq!(options.count_lines, true);
+    assert!(options.count_lines);
 }
 
 #[test]
 fn parse_color_output() {
     let arg = String::from("--color=always");
-    let mut options = Options::default();
-    options.color_output = false;
+    let mut options = Options {
+        color_output: false,
+        ..Default::default()
+    };
     options.parse_option(arg);
-    assert_eq!(options.color_output, true);
+    assert!(options.color_output);
 
     let arg = String::from("--color=never");
-    let mut options = Options::default();
-    options.color_output = true;
+    let mut options = Options {
+        color_output: true,
+        ..Default::default()
+    };
     options.parse_option(arg);
-    assert_eq!(options.color_output, false);
+    assert!(!options.color_output);
 
     let arg = String::from("--colour=always");
-    let mut options = Options::default();
-    options.color_output = false;
+    let mut options = Options {
+        color_output: false,
+        ..Default::default()
+    };
     options.parse_option(arg);
-    assert_eq!(options.color_output, true);
+    assert!(options.color_output);
 
     let arg = String::from("--colour=never");
+    let mut options = Options {
+        color_output: true,
+        ..Default::default()
+    };
+    options.parse_option(arg);
+    assert!(!options.color_output);
+
+    // tests don't write to a terminal
+    let arg = String::from("--color=auto");
+    let mut options = Options {
+        color_output: true,
+        ..Default::default()
+    };
+    options.parse_option(arg);
+    assert_eq!(options.color_output, Options::supports_color());
+
+    let arg = String::from("--color");
+    let mut options = Options {
+        color_output: true,
+        ..Default::default()
+    };
+    options.parse_option(arg);
+    assert_eq!(options.color_output, Options::supports_color());
+
+    let arg = String::from("--color=sometimes");
+    let mut options = Options::default();
+    assert!(options.parse_option(arg).is_err());
+}
+
+#[test]
+fn detect_color_support() {
+    use std::ffi::OsString;
+
+    let env = |vars: &'static [(&'static str, &'static str)]| {
+        move |name: &str| {
+            vars.iter()
+                .find(|(var, _)| *var == name)
+                .map(|(_, value)| OsString::from(value))
+        }
+    };
+
+    assert!(Options::detect_color(true, env(&[])));
+    assert!(!Options::detect_color(false, env(&[])));
+    assert!(Options::detect_color(true, env(&[("TERM", "xterm-256color")])));
+    assert!(!Options::detect_color(true, env(&[("TERM", "dumb")])));
+    assert!(!Options::detect_color(true, env(&[("NO_COLOR", "1")])));
+    assert!(Options::detect_color(true, env(&[("NO_COLOR", "")])));
+    assert!(!Options::detect_color(true, env(&[("CLICOLOR", "0")])));
+    assert!(Options::detect_color(true, env(&[("CLICOLOR", "1")])));
+    assert!(Options::detect_color(false, env(&[("CLICOLOR_FORCE", "1")])));
+    assert!(!Options::detect_color(false, env(&[("CLICOLOR_FORCE", "0")])));
+    assert!(!Options::detect_color(true, env(&[("CLICOLOR_FORCE", "1"), ("NO_COLOR", "1")])));
+    assert!(Options::detect_color(false, env(&[("CLICOLOR_FORCE", "1"), ("TERM", "dumb")])));
+}
+
+#[test]
+fn parse_json_output() {
+    let arg = String::from("--json");
+    let mut options = Options::default();
+    options.parse_option(arg);
+    assert_eq!(options.output_format, OutputFormat::Json);
+}
+
+#[test]
+fn parse_null_after_name() {
+    let arg = String::from("-Z");
+    let mut options = Options::default();
+    options.parse_option(arg);
+    assert!(options.null_after_name);
+
+    let arg = String::from("--null");
+    let mut options = Options::default();
+    options.parse_option(arg);
+    assert!(options.null_after_name);
+}
+
+#[test]
+fn parse_null_data() {
+    let arg = String::from("-z");
+    let mut options = Options::default();
+    options.parse_option(arg);
+    assert!(options.null_data);
+    assert_eq!(options.line_terminator(), "\0");
+
+    let arg = String::from("--null-data");
+    let mut options = Options::default();
+    options.parse_option(arg);
+    assert!(options.null_data);
+    assert_eq!(Options::default().line_terminator(), "\n");
+}
+
+#[test]
+fn parse_only_matching() {
+    let arg = String::from("-o");
+    let mut options = Options::default();
+    options.parse_option(arg);
+    assert!(options.only_matching);
+
+    let arg = String::from("--only-matching");
+    let mut options = Options::default();
+    options.parse_option(arg);
+    assert!(options.only_matching);
+}
+
+#[test]
+fn parse_replace() {
+    let arg = String::from("--replace=$1-${name}");
+    let mut options = Options::default();
+    options.parse_option(arg);
+    assert_eq!(options.replace, Some(String::from("$1-${name}")));
+
+    let arg = String::from("--replace=");
+    let mut options = Options::default();
+    options.parse_option(arg);
+    assert_eq!(options.replace, Some(String::new()));
+}
+
+#[test]
+fn parse_in_place() {
+    let arg = String::from("--in-place");
     let mut options = Options::default();
-    options.color_output = true;
     options.parse_option(arg);
-    assert_eq!(options.color_output, false);
-}
\ No newline at end of file
+    assert_eq!(options.in_place, Some(String::new()));
+
+    let arg = String::from("--in-place=.bak");
+    let mut options = Options::default();
+    options.parse_option(arg);
+    assert_eq!(options.in_place, Some(String::from(".bak")));
+}
+
+#[test]
+fn parse_diff() {
+    let arg = String::from("--diff");
+    let mut options = Options::default();
+    options.parse_option(arg);
+    assert!(options.diff);
+}
+
+#[test]
+fn parse_multiline() {
+    let arg = String::from("-U");
+    let mut options = Options::default();
+    options.parse_option(arg);
+    assert!(options.multiline);
+
+    let arg = String::from("--multiline");
+    let mut options = Options::default();
+    options.parse_option(arg);
+    assert!(options.multiline);
+
+    let arg = String::from("--multiline-dotall");
+    let mut options = Options::default();
+    options.parse_option(arg);
+    assert!(options.multiline_dotall);
+}
+
+#[test]
+fn parse_record_separator() {
+    let arg = String::from("--record-separator=(\n)\\d+:");
+    let mut options = Options::default();
+    options.parse_option(arg).unwrap();
+    assert_eq!(options.record_separator, Some(RecordSeparator(Regex::new("(\n)\\d+:").unwrap())));
+
+    let arg = String::from("--record-separator=(");
+    let mut options = Options::default();
+    assert!(options.parse_option(arg).is_err());
+
+    for separator in ["", "x*", "\\n|"] {
+        let arg = format!("--record-separator={}", separator);
+        let mut options = Options::default();
+        assert!(options.parse_option(arg).is_err());
+        assert_eq!(options.record_separator, None);
+    }
+
+    let arg = String::from("--paragraph");
+    let mut options = Options::default();
+    options.parse_option(arg).unwrap();
+    assert_eq!(options.record_separator.unwrap().0.as_str(), RecordSeparator::PARAGRAPH);
+}
+
+#[test]
+fn parse_crlf() {
+    let arg = String::from("--crlf");
+    let mut options = Options::default();
+    options.parse_option(arg);
+    assert_eq!(options.crlf, Some(true));
+
+    let arg = String::from("--no-crlf");
+    let mut options = Options::default();
+    options.parse_option(arg);
+    assert_eq!(options.crlf, Some(false));
+}
+
+#[test]
+fn parse_debug() {
+    let arg = String::from("--debug");
+    let mut options = Options::default();
+    options.parse_option(arg);
+    assert!(options.debug);
+}
+
+#[test]
+fn parse_colors_theme() {
+    let arg = String::from("--colors-theme=light");
+    let mut options = Options::default();
+    options.parse_option(arg).unwrap();
+    assert_eq!(options.colors, Colors::get_themed_colors(Colors::theme("light").unwrap()));
+
+    let arg = String::from("--colors-theme=neon");
+    let mut options = Options::default();
+    assert!(options.parse_option(arg).is_err());
+}
+
+#[test]
+fn parse_hyperlink_format() {
+    let arg = String::from("--hyperlink-format=vscode");
+    let mut options = Options::default();
+    options.parse_option(arg).unwrap();
+    assert_eq!(options.hyperlink_format, Some(String::from("vscode://file{path}:{line}:{column}")));
+
+    let arg = String::from("--hyperlink-format=file://{host}{path}#{line}");
+    let mut options = Options::default();
+    options.parse_option(arg).unwrap();
+    assert_eq!(options.hyperlink_format, Some(String::from("file://{host}{path}#{line}")));
+
+    let arg = String::from("--hyperlink-format=none");
+    let mut options = Options::default();
+    options.parse_option(arg).unwrap();
+    assert_eq!(options.hyperlink_format, None);
+
+    let arg = String::from("--hyperlink-format=file://{host}");
+    let mut options = Options::default();
+    assert!(options.parse_option(arg).is_err());
+}
+
+#[test]
+fn parse_heading() {
+    let mut options = Options::default();
+    options.parse_option(String::from("--heading")).unwrap();
+    assert!(options.heading);
+
+    options.parse_option(String::from("--no-heading")).unwrap();
+    assert!(!options.heading);
+}
+
+#[test]
+fn parse_vimgrep() {
+    let mut options = Options::default();
+    options.parse_option(String::from("--vimgrep")).unwrap();
+    assert!(options.vimgrep);
+}
+
+#[test]
+fn parse_rules() {
+    let mut options = Options::default();
+    options.parse_option(String::from("--rules=res/test/lint.rules")).unwrap();
+    assert_eq!(options.rules, Some(String::from("res/test/lint.rules")));
+}
+
+#[test]
+fn parse_baselines() {
+    let mut options = Options::default();
+    options.parse_option(String::from("--baseline=old.baseline")).unwrap();
+    options.parse_option(String::from("--write-baseline=new.baseline")).unwrap();
+    assert_eq!(options.baseline, Some(String::from("old.baseline")));
+    assert_eq!(options.write_baseline, Some(String::from("new.baseline")));
+}
+
+#[test]
+fn parse_extract() {
+    let mut options = Options::default();
+    options.parse_option(String::from("--extract=csv")).unwrap();
+    assert_eq!(options.extract, Some(ExtractFormat::Csv));
+
+    options.parse_option(String::from("--extract=tsv")).unwrap();
+    options.parse_option(String::from("--extract-columns=path,line")).unwrap();
+    assert_eq!(options.extract, Some(ExtractFormat::Tsv));
+    assert_eq!(options.extract_columns, vec!(ExtractColumn::Path, ExtractColumn::Line));
+
+    assert!(options.parse_option(String::from("--extract=xlsx")).is_err());
+    assert!(options.parse_option(String::from("--extract-columns=path,column")).is_err());
+}
+
+#[test]
+fn parse_tally() {
+    let mut options = Options::default();
+    options.parse_option(String::from("--tally")).unwrap();
+    assert_eq!(options.tally, Some(String::new()));
+
+    options.parse_option(String::from("--tally=status")).unwrap();
+    options.parse_option(String::from("--top=10")).unwrap();
+    assert_eq!(options.tally, Some(String::from("status")));
+    assert_eq!(options.top, Some(10));
+
+    assert!(options.parse_option(String::from("--top=ten")).is_err());
+}
+
+#[test]
+fn parse_counts() {
+    let mut options = Options::default();
+    options.parse_option(String::from("--count-matches")).unwrap();
+    assert!(options.count_lines);
+    assert!(options.count_matches);
+
+    let mut options = Options::default();
+    options.parse_option(String::from("--total")).unwrap();
+    options.parse_option(String::from("--hide-zero")).unwrap();
+    assert!(options.count_lines);
+    assert!(options.total);
+    assert!(!options.include_zero);
+
+    options.parse_option(String::from("--include-zero")).unwrap();
+    assert!(options.include_zero);
+}
+
+#[test]
+fn parse_field_separators() {
+    let mut options = Options::default();
+    options.parse_option(String::from("-T")).unwrap();
+    assert!(options.initial_tab);
+
+    let mut options = Options::default();
+    options.parse_option(String::from("--initial-tab")).unwrap();
+    options.parse_option(String::from("--field-match-separator= => ")).unwrap();
+    options.parse_option(String::from("--field-context-separator=")).unwrap();
+    assert!(options.initial_tab);
+    assert_eq!(options.field_match_separator, " => ");
+    assert_eq!(options.field_context_separator, "");
+}
+
+#[test]
+fn parse_output_format() {
+    let mut options = Options::default();
+    options.parse_option(String::from("--format=sarif")).unwrap();
+    assert_eq!(options.output_format, OutputFormat::Sarif);
+
+    options.parse_option(String::from("--format=json")).unwrap();
+    assert_eq!(options.output_format, OutputFormat::Json);
+
+    options.parse_option(String::from("--format=text")).unwrap();
+    assert_eq!(options.output_format, OutputFormat::Text);
+
+    options.parse_option(String::from("--format={path}:{line}:{col}: {match} | {text}")).unwrap();
+    assert_eq!(
+        options.output_format,
+        OutputFormat::Template(Template::parse("{path}:{line}:{col}: {match} | {text}").unwrap())
+    );
+
+    assert!(options.parse_option(String::from("--format={unclosed")).is_err());
+    assert!(options.parse_option(String::from("--format=jsn")).is_err());
+    assert!(options.parse_option(String::from("--format=\\{path\\}")).is_err());
+}
+
+#[test]
+fn parse_stats() {
+    let mut options = Options::default();
+    options.parse_option(String::from("--stats")).unwrap();
+    assert!(options.stats);
+}
+
+#[test]
+fn parse_show_pattern() {
+    let mut options = Options::default();
+    options.parse_option(String::from("--show-pattern")).unwrap();
+    assert_eq!(options.show_pattern, Some(PatternLabel::Index));
+
+    options.parse_option(String::from("--show-pattern=pattern")).unwrap();
+    assert_eq!(options.show_pattern, Some(PatternLabel::Pattern));
+
+    options.parse_option(String::from("--show-pattern=index")).unwrap();
+    assert_eq!(options.show_pattern, Some(PatternLabel::Index));
+
+    assert!(options.parse_option(String::from("--show-pattern=name")).is_err());
+}
+
+#[test]
+fn parse_pattern_colors() {
+    let mut options = Options::default();
+    assert!(!options.uses_pattern_colors());
+    assert_eq!(options.pattern_color(0), None);
+
+    options.parse_option(String::from("--pattern-colors")).unwrap();
+    assert!(options.uses_pattern_colors());
+    assert_eq!(options.pattern_color(0), Some("1;31"));
+    assert_eq!(options.pattern_color(7), Some("1;32"));
+
+    options.parse_option(String::from("--pattern-colors=red:bold,blue")).unwrap();
+    assert_eq!(options.pattern_palette, vec!(String::from("31"), String::from("1;34")));
+    assert!(options.parse_option(String::from("--pattern-colors=red:nope")).is_err());
+
+    let mut options = Options::default();
+    assert!(options.parse_option(String::from("--color-for=red")).is_err());
+
+    options.parse_option(String::from("-edew")).unwrap();
+    options.parse_option(String::from("-eworld")).unwrap();
+    options.parse_option(String::from("--color-for=green")).unwrap();
+    assert_eq!(options.pattern_colors, vec!(None, Some(String::from("32"))));
+    assert_eq!(options.pattern_color(0), None);
+    assert_eq!(options.pattern_color(1), Some("32"));
+    assert!(options.uses_pattern_colors());
+    assert!(options.parse_option(String::from("--color-for=nope")).is_err());
+    assert!(options.parse_option(String::from("--color-for=#€")).is_err());
+    assert!(options.parse_option(String::from("--pattern-colors=#€")).is_err());
+}
+
+#[test]
+fn parse_pattern_labels() {
+    let mut options = Options::default();
+    assert!(options.parse_option(String::from("--label-for=unwrap")).is_err());
+
+    options.parse_option(String::from("-e\\.unwrap\\(")).unwrap();
+    options.parse_option(String::from("--label-for=unwrap")).unwrap();
+    options.parse_option(String::from("-etodo!")).unwrap();
+    assert!(options.parse_option(String::from("--label-for=")).is_err());
+
+    assert_eq!(options.pattern_labels, vec!(Some(String::from("unwrap"))));
+    assert_eq!(options.pattern_label(0), "unwrap");
+    assert_eq!(options.pattern_label(1), "todo!");
+}
@@ -3,211 +3,601 @@ mod test;
 use crate::source;
 use crate::options;
 use crate::colors;
+use crate::sink;
+use crate::hyperlink;
 
 use colors::Colors;
 use source::Source;
-use options::Options;
-use regex::Matches;
-use std::iter::{zip, Peekable};
+use options::{Options, PatternLabel};
+use sink::{Sink, SinkLine, SourceStats};
 use std::io::{stdout, Write};
 
 /// Default output buffer size.
 const BUFFER_SIZE: usize = 4096;
-const ANSI_RESET: &'static str = "\x1b[0m";
-const ANSI_ESCAPE: &'static str = "\x1b[";
-const ANSI_END: &'static str = "m";
+const ANSI_RESET: &str = "\x1b[0m";
+const ANSI_ESCAPE: &str = "\x1b[";
+const ANSI_END: &str = "m";
+/// Minimum width of line numbers when aligning content with `initial_tab`.
+const LINE_NUMBER_WIDTH: usize = 4;
+/// Erases from the cursor to the end of the line, see [Colors::no_erase].
+const ANSI_ERASE_LINE: &str = "\x1b[K";
+
+/// Buffers output and writes it to a destination once the buffer is full, or
+/// when flushed. Shared by the sinks that write results.
+pub struct BufferedWriter {
+    /// Internal buffer for output content.
+    buffer: String,
+    /// A writable destination for content to be written to.
+    destination: Box<dyn Write>,
+}
+
+impl Default for BufferedWriter {
+    /// Creates new instance of BufferedWriter with default
+    /// buffer size and stdout as destination.
+    fn default() -> BufferedWriter {
+        BufferedWriter::new(Box::from(stdout()))
+    }
+}
+
+impl BufferedWriter {
+    /// Creates new instance of BufferedWriter with default
+    /// buffer size writing to `destination`.
+    pub fn new(destination: Box<dyn Write>) -> BufferedWriter {
+        BufferedWriter {
+            buffer: String::with_capacity(BUFFER_SIZE),
+            destination,
+        }
+    }
+
+    /// Appends `text` to the buffer, writing the buffer once it is full.
+    pub fn push_str(&mut self, text: &str) {
+        self.buffer.push_str(text);
+
+        if self.buffer.len() >= BUFFER_SIZE {
+            self.write_and_flush();
+        }
+    }
+
+    /// Appends `c` to the buffer, writing the buffer once it is full.
+    pub fn push(&mut self, c: char) {
+        self.buffer.push(c);
+
+        if self.buffer.len() >= BUFFER_SIZE {
+            self.write_and_flush();
+        }
+    }
+
+    /// Writes buffer to destination and flushes.
+    pub fn write_and_flush(&mut self) {
+        write!(
+            self.destination,
+            "{}",
+            self.buffer
+        ).expect("grepRS: Could not write to destination!");
+
+        self.destination.flush()
+            .expect("grepRS: Could not flush output buffer!");
+
+        self.buffer = String::with_capacity(BUFFER_SIZE);
+    }
+}
+
+#[cfg(test)]
+impl BufferedWriter {
+    /// Creates new instance of BufferedWriter writing to a vector instead of
+    /// stdout, used by tests.
+    pub(crate) fn for_test() -> BufferedWriter {
+        BufferedWriter::new(Box::new(Vec::<u8>::new()))
+    }
+
+    /// Returns the content that hasn't been written yet.
+    pub(crate) fn as_str(&self) -> &str {
+        &self.buffer
+    }
+
+    /// Discards the content that hasn't been written yet.
+    pub(crate) fn clear(&mut self) {
+        self.buffer.clear();
+    }
+}
 
 /// Contains methods for buffering and writing output.
 /// Due to the private nature of the struct fields "integration"
 /// testing can be found in output/test.rs.
 pub struct OutputBuffer {
-    /// Internal buffer for output content.
-    buffer: String,
-    /// A writable destination for content to be written to.
-    destination: Box<dyn Write>,
+    /// Buffers output content and writes it to stdout.
+    writer: BufferedWriter,
+    /// If the heading of the current source has been written, see [Options::heading].
+    heading_written: bool,
+    /// Number of sources a heading has been written for.
+    groups_written: usize,
+    /// Statistics summed over every source, used for [total](Options::total) counts.
+    totals: SourceStats,
+    /// Resolved path of the current source used as hyperlink target, see
+    /// [hyperlink::resolve_path]. Only set when hyperlinks are written.
+    hyperlink_path: Option<String>,
 }
 
-impl OutputBuffer {
+impl Default for OutputBuffer {
     /// Creates new instance of OutputBuffer with default
     /// buffer size and stdout as destination.
-    pub fn default() -> OutputBuffer {
+    fn default() -> OutputBuffer {
         OutputBuffer {
-            buffer: String::with_capacity(BUFFER_SIZE),
-            destination: Box::from(stdout()),
+            writer: BufferedWriter::default(),
+            heading_written: false,
+            groups_written: 0,
+            totals: SourceStats::default(),
+            hyperlink_path: None,
         }
     }
+}
 
-    /// Writes results of search on a [Source] to the
-    /// internal output buffer.
-    pub fn append_source_matches(
-        &mut self,
-        options: &Options,
-        source: &Source,
-        source_matches: Vec<Matches>
-    ) {
-        let source_lines = source.data.split("\n");
-        let line_matches = zip(source_lines, source_matches);
+#[cfg(test)]
+impl OutputBuffer {
+    /// Creates new instance of OutputBuffer with default buffer
+    /// size and `destination` instead of stdout, used by tests.
+    pub(crate) fn with_destination(destination: Vec<u8>) -> OutputBuffer {
+        OutputBuffer {
+            writer: BufferedWriter::new(Box::new(destination)),
+            ..OutputBuffer::default()
+        }
+    }
+}
+
+impl Sink for OutputBuffer {
+    fn begin_source(&mut self, options: &Options, source: &Source) {
+        self.heading_written = false;
+        self.hyperlink_path = if options.color_output && options.hyperlink_format.is_some() {
+            hyperlink::resolve_path(&source.path)
+        } else {
+            None
+        };
+    }
 
-        for (line, matches) in line_matches {
-            let mut matches = matches.peekable();
-            let has_match = matches.peek().is_some();
+    /// Writes a selected line to the internal output buffer. Nothing is
+    /// written when counting matching lines.
+    fn matched_line(&mut self, options: &Options, source: &Source, line: &SinkLine) {
+        // exit immediately if matching line is found
+        // and silent mode is on
+        if options.silent {
+            std::process::exit(0);
+        }
 
-            // non-inverted matching
-            let line: Option<String> = if has_match && !options.invert_match {
-                if options.color_output {
-                    Some(Self::apply_match_color(line, &mut matches, &options.colors))
-                } else {
-                    Some(String::from(line))
-                }
-            }
-            // inverted matching
-            else if !has_match && options.invert_match && line != "" {
-                Some(String::from(line))
-            }
-            else {
-                None
-            };
+        if options.count_lines {
+            return;
+        }
 
-            // exit immediately if matching line is found
-            // and silent mode is on
-            if options.silent && line.is_some() {
-                std::process::exit(0);
+        if options.vimgrep {
+            self.append_vimgrep_line(options, &source.path, line);
+            return;
+        }
+
+        self.append_colored_line(options, &source.path, line, false);
+    }
+
+    /// Writes a context line to the internal output buffer. Context lines
+    /// are left out of `vimgrep` output.
+    fn context_line(&mut self, options: &Options, source: &Source, line: &SinkLine) {
+        if options.silent || options.count_lines || options.vimgrep {
+            return;
+        }
+
+        self.append_colored_line(options, &source.path, line, true);
+    }
+
+    /// Appends results of matching line search to output buffer
+    /// when counting matching lines or matches. Counts are summed instead when
+    /// counting a total, sources without any are skipped unless `include_zero` is set.
+    fn end_source(&mut self, options: &Options, source: &Source, stats: &SourceStats) {
+        if options.count_lines {
+            if options.total {
+                self.totals.matched_lines += stats.matched_lines;
+                self.totals.matches += stats.matches;
+                return;
             }
 
-            if let Some(line) = line {
-                // apply selected line coloring
-                if &options.colors.selected_line != "" && options.color_output {
-                    self.append_line(
-                        options,
-                        &source.path,
-                        Self::apply_ansi_code(
-                            &line,
-                            &options.colors.selected_line
-                        ).as_str()
-                    );
-                } else {
-                    self.append_line(options, &source.path, &line);
-                }
+            let count = Self::count(options, stats);
+            if count > 0 || options.include_zero {
+                self.append_source_counts(options, source, count);
             }
         }
     }
 
+    fn finish(&mut self, options: &Options) {
+        self.append_total(options);
+
+        if !options.silent {
+            self.write_and_flush();
+        }
+    }
+}
+
+impl OutputBuffer {
     /// Appends results of matching line search to output buffer.
     pub fn append_source_counts(
         &mut self,
         options: &Options,
         source: &Source,
-        matching_lines: usize,
+        count: usize,
     ) {
-        let line = format!("{}\n", matching_lines);
+        self.append_line(options, &source.path, &count.to_string());
+    }
+
+    /// Appends the count summed over every source when counting a total.
+    pub fn append_total(&mut self, options: &Options) {
+        if options.count_lines && options.total {
+            let total = format!("{}\n", Self::count(options, &self.totals));
+            self.append_text(&total);
+        }
+    }
 
-        self.append_line(options, &source.path, &line);
+    /// Returns the matches of `stats` when counting matches, otherwise the
+    /// selected lines. Inverted searches always count lines.
+    fn count(options: &Options, stats: &SourceStats) -> usize {
+        if options.count_matches && !options.invert_match {
+            stats.matches
+        } else {
+            stats.matched_lines
+        }
     }
 
     /// Writes buffer to destination and flushes.
     pub fn write_and_flush(&mut self) {
-        write!(
-            self.destination,
-            "{}",
-            self.buffer
-        ).expect("grepRS: Could not write to destination!");
+        self.writer.write_and_flush();
+    }
 
-        self.destination.flush()
-            .expect("grepRS: Could not flush output buffer!");
+    /// Writes already formatted text to the buffer as is.
+    pub fn append_text(&mut self, text: &str) {
+        self.writer.push_str(text);
+    }
 
-        self.buffer = String::with_capacity(BUFFER_SIZE);
+    /// Writes a selected or `context` line reported by the search to the buffer,
+    /// highlighting matches and the rest of the line with the colors for its kind.
+    /// Only the matches are written, one per line, when `only_matching` is set.
+    fn append_colored_line(&mut self, options: &Options, path: &str, line: &SinkLine, context: bool) {
+        let colors = &options.colors;
+        let (match_color, separator) = if context {
+            (&colors.context_match, &options.field_context_separator)
+        } else {
+            (&colors.selected_match, &options.field_match_separator)
+        };
+
+        // selected and context line colors are swapped by `rv` when inverting
+        let line_color = if context == (options.invert_match && colors.reverse) {
+            &colors.selected_line
+        } else {
+            &colors.context_line
+        };
+
+        let line_terminator = options.line_terminator();
+
+        if options.only_matching {
+            for index in 0..line.matches.len() {
+                let text = line.match_text(index);
+                if text.is_empty() {
+                    continue;
+                }
+
+                let mut text = if options.color_output {
+                    Self::apply_ansi_code(text, Self::span_color(options, line, index, match_color), &options.colors)
+                } else {
+                    String::from(text)
+                };
+
+                if options.show_pattern.is_some() {
+                    let patterns: Vec<usize> = line.match_patterns.get(index).copied().flatten().into_iter().collect();
+                    text.insert_str(0, &Self::pattern_field(options, &patterns, separator));
+                }
+
+                self.append_terminated_line(options, path, Some(line), separator, &text, line_terminator);
+            }
+
+            return;
+        }
+
+        if options.replace.is_some() && Self::replaced_entirely(options, line) {
+            return;
+        }
+
+        // preserve the terminator of the line unless it is the final line without one
+        let terminator = if line.terminator.is_empty() {
+            line_terminator
+        } else {
+            line.terminator
+        };
+
+        let mut text = if options.color_output && !line.matches.is_empty() {
+            Self::apply_match_color(options, line, match_color, line_color)
+        } else if !line.replacements.is_empty() {
+            line.replaced_text()
+        } else {
+            String::from(line.text)
+        };
+
+        // apply line coloring
+        if !line_color.is_empty() && options.color_output {
+            text = Self::apply_ansi_code(&text, line_color, &options.colors);
+        }
+
+        if options.show_pattern.is_some() && !context {
+            text.insert_str(0, &Self::pattern_field(options, &line.patterns, separator));
+        }
+
+        self.append_terminated_line(options, path, Some(line), separator, &text, terminator);
+    }
+
+    /// Checks if all of `line` is covered by a multiline match that started on a
+    /// previous line. Such lines are part of the replacement of that match.
+    fn replaced_entirely(options: &Options, line: &SinkLine) -> bool {
+        if line.matches.is_empty() {
+            // without inverting only lines inside of a multiline match have no matches
+            return options.multiline && !options.invert_match;
+        }
+
+        line.continues_match && line.matches.len() == 1 && line.matches[0].end == line.text.len()
+    }
+
+    /// Builds the field naming `patterns` for [show_pattern](Options::show_pattern),
+    /// separated by commas and followed by `separator`.
+    fn pattern_field(options: &Options, patterns: &[usize], separator: &str) -> String {
+        let labels: Vec<String> = patterns.iter()
+            .map(|index| match options.show_pattern {
+                Some(PatternLabel::Pattern) => String::from(options.pattern_label(*index)),
+                _ => (index + 1).to_string(),
+            })
+            .collect();
+
+        labels.join(",") + separator
+    }
+
+    /// Writes `path:line:column:text` for every match inside of `line`, or once
+    /// for lines without matches. Columns are 1-based byte offsets of matches.
+    /// Multiline matches are only written for the line they start on.
+    /// Output is never colored and always includes the file name.
+    fn append_vimgrep_line(&mut self, options: &Options, path: &str, line: &SinkLine) {
+        let inside_match = line.matches.is_empty() && options.multiline && !options.invert_match;
+        if inside_match || (line.continues_match && line.matches.len() == 1) {
+            return;
+        }
+
+        let path = if path != "-" {
+            path
+        } else {
+            "(standard input)"
+        };
+
+        let text = if line.replacements.is_empty() {
+            String::from(line.text)
+        } else {
+            line.replaced_text()
+        };
+
+        for index in usize::from(line.continues_match)..usize::max(line.matches.len(), 1) {
+            let column = line.matches.get(index).map_or(1, |span| span.start + 1);
+
+            let text = if options.only_matching && index < line.matches.len() {
+                line.match_text(index)
+            } else {
+                &text
+            };
+
+            self.writer.push_str(&format!("{}:{}:{}:{}", path, line.line_number, column, text));
+            self.writer.push_str(options.line_terminator());
+        }
     }
 
     /// Writes a single line to buffer. If `line` doesn't end
     /// with a newline char a newline will be added to the buffer.
     fn append_line(&mut self, options: &Options, path: &str, line: &str) {
-        if options.file_prefix {
-            self.append_file_path(path, options.color_output, &options.colors);
-            self.append_separator(options.color_output, &options.colors);
+        let terminator = if line.ends_with('\n') { "" } else { "\n" };
+
+        self.append_terminated_line(options, path, None, &options.field_match_separator, line, terminator);
+    }
+
+    /// Writes a single line to buffer followed by `terminator`. Lines of a
+    /// source never contain their terminator, so it is always written. `sink_line` is
+    /// the line of the source that is written, if any. Lines of a source are
+    /// grouped under a heading instead of prefixed with the file name when
+    /// [heading](Options::heading) is set. Fields of the prefix are followed
+    /// by `separator`, and the prefix by a tab when `initial_tab` is set.
+    fn append_terminated_line(
+        &mut self,
+        options: &Options,
+        path: &str,
+        sink_line: Option<&SinkLine>,
+        separator: &str,
+        line: &str,
+        terminator: &str
+    ) {
+        let has_prefix = match sink_line {
+            Some(sink_line) if options.heading => {
+                self.append_heading(options, path, sink_line);
+                self.append_line_number(options, sink_line.line_number);
+                self.append_separator(options, separator);
+                true
+            },
+            _ if options.file_prefix => {
+                self.append_file_path(options, path, sink_line);
+
+                if options.null_after_name {
+                    self.writer.push('\0');
+                } else {
+                    self.append_separator(options, separator);
+                }
+                true
+            },
+            _ => false,
+        };
+
+        if has_prefix && options.initial_tab {
+            self.writer.push('\t');
         }
 
-        self.buffer.push_str(line);
+        self.writer.push_str(line);
+        self.writer.push_str(terminator);
+    }
 
-        if !self.buffer.ends_with("\n") {
-            self.buffer.push_str("\n");
+    /// Writes the heading of a source before its first line. Headings are
+    /// only written when file names are shown, groups of lines of different
+    /// sources are separated by a blank line.
+    fn append_heading(&mut self, options: &Options, path: &str, line: &SinkLine) {
+        if self.heading_written {
+            return;
         }
 
-        if self.buffer.len() >= BUFFER_SIZE {
-            self.write_and_flush();
+        if self.groups_written > 0 {
+            self.writer.push('\n');
         }
+
+        if options.file_prefix {
+            self.append_file_path(options, path, Some(line));
+            self.writer.push('\n');
+        }
+
+        self.heading_written = true;
+        self.groups_written += 1;
     }
 
-    /// Adds a file prefix to output buffer for current line.
-    fn append_file_path(&mut self, path: &str, color: bool, colors: &Colors) {
-        let path = if path != "-" {
+    /// Adds the number of the current line to output buffer. Line numbers are
+    /// right aligned to a minimum width when `initial_tab` is set.
+    fn append_line_number(&mut self, options: &Options, line_number: usize) {
+        let width = if options.initial_tab { LINE_NUMBER_WIDTH } else { 0 };
+        let mut line_number = format!("{:>width$}", line_number, width = width);
+
+        if options.color_output {
+            line_number = Self::apply_ansi_code(&line_number, &options.colors.line_number, &options.colors);
+        }
+
+        self.writer.push_str(&line_number);
+    }
+
+    /// Adds a file prefix to output buffer for current line. The prefix is
+    /// wrapped in a hyperlink to `line` when output is colored and a
+    /// [hyperlink format](Options::hyperlink_format) is set.
+    fn append_file_path(&mut self, options: &Options, path: &str, line: Option<&SinkLine>) {
+        let display_path = if path != "-" {
             path
         } else {
             "(standard input)"
         };
 
-        let mut path = String::from(path);
+        let mut display_path = String::from(display_path);
 
-        if color {
-            path = Self::apply_ansi_code(
-                &path,
-                &colors.file_name
+        if options.color_output {
+            display_path = Self::apply_ansi_code(
+                &display_path,
+                &options.colors.file_name,
+                &options.colors
             );
+
+            if let Some(url) = self.hyperlink_url(options, line) {
+                display_path = hyperlink::wrap(&display_path, &url);
+            }
         }
 
-        self.buffer.push_str(path.as_str());
+        self.writer.push_str(display_path.as_str());
     }
 
-    /// Applies color to matches inside a line. Only supports default color currently.
-    fn apply_match_color(line: &str, matches: &mut Peekable<Matches>, colors: &Colors) -> String {
+    /// Builds the hyperlink target for a line of the current source. Links point
+    /// to the first match of the line, or to the first line when there is no line.
+    fn hyperlink_url(&self, options: &Options, line: Option<&SinkLine>) -> Option<String> {
+        let format = options.hyperlink_format.as_ref()?;
+        let path = self.hyperlink_path.as_ref()?;
+
+        let (line_number, column) = match line {
+            Some(line) => (
+                line.line_number,
+                line.matches.first().map_or(1, |span| span.start + 1)
+            ),
+            None => (1, 1),
+        };
+
+        Some(hyperlink::build_url(format, path, line_number, column))
+    }
+
+    /// Applies `match_color` to matches inside a line, or to their replacements
+    /// when there are any. Matches of patterns with a color of their own use that
+    /// color instead, see [span_color](Self::span_color). When `line_color` is set
+    /// it is restored after each match so it covers the intermediate text.
+    fn apply_match_color(options: &Options, line: &SinkLine, match_color: &str, line_color: &str) -> String {
+        let colors = &options.colors;
         let mut colored_line = String::new();
 
         let mut previous = 0;
-        while let Some(match_obj) = matches.next() {
-            let start = match_obj.start();
-            let end = match_obj.end();
-
-            colored_line.push_str(&line[previous..start]);
+        for (index, span) in line.matches.iter().enumerate() {
+            colored_line.push_str(&line.text[previous..span.start]);
             let colored_match = Self::apply_ansi_code(
-                &line[start..end],
-                &colors.selected_match
+                line.match_text(index),
+                Self::span_color(options, line, index, match_color),
+                colors
             );
             colored_line.push_str(colored_match.as_str());
 
-            if colors.selected_line != "" {
-                // apply selected line coloring on intermediate text
-                colored_line.push_str(
-                    (ANSI_ESCAPE.to_owned() + &colors.selected_line + ANSI_END).as_str()
-                );
+            if !line_color.is_empty() {
+                // apply line coloring on intermediate text
+                colored_line.push_str(Self::start_color(line_color, colors).as_str());
             }
 
-            previous = end;
+            previous = span.end;
         }
 
-        if line.len() != previous {
-            colored_line.push_str(&line[previous..]);
+        if line.text.len() != previous {
+            colored_line.push_str(&line.text[previous..]);
         }
 
         colored_line
     }
 
-    /// Appends a separator to delimitate file names and content lines.
-    fn append_separator(&mut self, color: bool, colors: &Colors) {
-        let mut separator = String::from(":\t");
+    /// Returns the color of the match at `index` of `line`, the
+    /// [pattern color](Options::pattern_color) of the pattern that produced it
+    /// if it has one, otherwise `match_color`.
+    fn span_color<'a>(options: &'a Options, line: &SinkLine, index: usize, match_color: &'a str) -> &'a str {
+        line.match_patterns.get(index)
+            .copied()
+            .flatten()
+            .and_then(|pattern| options.pattern_color(pattern))
+            .unwrap_or(match_color)
+    }
+
+    /// Appends a separator to delimitate fields of a prefix and content lines.
+    fn append_separator(&mut self, options: &Options, separator: &str) {
+        let mut separator = String::from(separator);
 
-        if color {
-            separator = Self::apply_ansi_code(&separator, &colors.separator);
+        if options.color_output {
+            separator = Self::apply_ansi_code(&separator, &options.colors.separator, &options.colors);
         }
 
-        self.buffer.push_str(&separator);
+        self.writer.push_str(&separator);
     }
 
     /// Applies an ANSI code to a given content string and returns
-    /// a handle to a heap allocated string.
-    fn apply_ansi_code(content: &str, ansi_code: &str) -> String {
+    /// a handle to a heap allocated string. Content is left as is
+    /// when `ansi_code` is empty.
+    pub fn apply_ansi_code(content: &str, ansi_code: &str, colors: &Colors) -> String {
+        if ansi_code.is_empty() {
+            return String::from(content);
+        }
+
+        Self::start_color(ansi_code, colors)
+            + content
+            + ANSI_RESET
+            + Self::erase_line(colors)
+    }
+
+    /// Builds the escape sequence that starts coloring with an ANSI code.
+    fn start_color(ansi_code: &str, colors: &Colors) -> String {
         ANSI_ESCAPE.to_owned()
             + ansi_code
             + ANSI_END
-            + content
-            + ANSI_RESET
+            + Self::erase_line(colors)
+    }
+
+    /// Returns the erase to end of line sequence unless disabled by `ne`.
+    fn erase_line(colors: &Colors) -> &'static str {
+        if colors.no_erase {
+            ""
+        } else {
+            ANSI_ERASE_LINE
+        }
     }
 }
@@ -10,142 +10,754 @@ use super::*;
 #[test]
 fn default_buffer_capacity() {
     let default_output_buffer = OutputBuffer::default();
-    assert_eq!(default_output_buffer.buffer.capacity(), BUFFER_SIZE);
+    assert_eq!(default_output_buffer.writer.buffer.capacity(), BUFFER_SIZE);
 }
 
 #[test]
 fn append_file_prefix_to_buffer() {
-    let mut output_buffer = OutputBuffer {
-        buffer: String::with_capacity(BUFFER_SIZE),
-        destination: Box::new(Vec::<u8>::new())
-    };
-
-    output_buffer.append_file_path("file path", false, &Colors::default());
-    assert_eq!(output_buffer.buffer, "file path");
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
 
-    let mut output_buffer = OutputBuffer {
-        buffer: String::with_capacity(BUFFER_SIZE),
-        destination: Box::new(Vec::<u8>::new())
+    let mut options = Options {
+        color_output: false,
+        ..Default::default()
     };
+    output_buffer.append_file_path(&options, "file path", None);
+    assert_eq!(output_buffer.writer.as_str(), "file path");
 
-    output_buffer.append_file_path("-", false, &Colors::default());
-    assert_eq!(output_buffer.buffer, "(standard input)");
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
 
-    let mut output_buffer = OutputBuffer {
-        buffer: String::with_capacity(BUFFER_SIZE),
-        destination: Box::new(Vec::<u8>::new())
-    };
+    output_buffer.append_file_path(&options, "-", None);
+    assert_eq!(output_buffer.writer.as_str(), "(standard input)");
 
-    output_buffer.append_file_path("file path", true, &Colors::default());
-    assert_eq!(output_buffer.buffer, "\x1b[32mfile path\x1b[0m");
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
 
-    let mut output_buffer = OutputBuffer {
-        buffer: String::with_capacity(BUFFER_SIZE),
-        destination: Box::new(Vec::<u8>::new())
-    };
+    options.color_output = true;
+    options.colors = Colors::default();
+    output_buffer.append_file_path(&options, "file path", None);
+    assert_eq!(output_buffer.writer.as_str(), "\x1b[32m\x1b[Kfile path\x1b[0m\x1b[K");
 
-    output_buffer.append_file_path("-", true, &Colors::default());
-    assert_eq!(output_buffer.buffer, "\x1b[32m(standard input)\x1b[0m");
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    output_buffer.append_file_path(&options, "-", None);
+    assert_eq!(output_buffer.writer.as_str(), "\x1b[32m\x1b[K(standard input)\x1b[0m\x1b[K");
 }
 
 #[test]
 fn append_line_to_buffer() {
     let line = "output line\n";
-    let mut output_buffer = OutputBuffer {
-        buffer: String::with_capacity(BUFFER_SIZE),
-        destination: Box::new(Vec::<u8>::new())
-    };
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
 
     output_buffer.append_line(&Options::default(), "-", line);
-    assert_eq!(output_buffer.buffer, "output line\n");
+    assert_eq!(output_buffer.writer.as_str(), "output line\n");
 
     let line = "output line";
-    let mut output_buffer = OutputBuffer {
-        buffer: String::with_capacity(BUFFER_SIZE),
-        destination: Box::new(Vec::<u8>::new())
-    };
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
 
     output_buffer.append_line(&Options::default(), "-", line);
-    assert_eq!(output_buffer.buffer, "output line\n");
+    assert_eq!(output_buffer.writer.as_str(), "output line\n");
 
     let line = "output line\n";
-    let mut output_buffer = OutputBuffer {
-        buffer: String::with_capacity(BUFFER_SIZE),
-        destination: Box::new(Vec::<u8>::new())
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+    let options = Options {
+        color_output: true,
+        file_prefix: true,
+        ..Default::default()
     };
-    let mut options = Options::default();
-    options.color_output = true;
-    options.file_prefix = true;
 
     output_buffer.append_line(&options, "-", line);
-    assert_eq!(output_buffer.buffer, "\u{1b}[32m(standard input)\u{1b}[0m\u{1b}[35m:\t\u{1b}[0moutput line\n");
+    assert_eq!(output_buffer.writer.as_str(), "\u{1b}[32m\u{1b}[K(standard input)\u{1b}[0m\u{1b}[K\u{1b}[35m\u{1b}[K:\u{1b}[0m\u{1b}[Koutput line\n");
 
     let line = "output line\n";
-    let mut output_buffer = OutputBuffer {
-        buffer: String::with_capacity(BUFFER_SIZE),
-        destination: Box::new(Vec::<u8>::new())
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+    let options = Options {
+        color_output: false,
+        file_prefix: true,
+        ..Default::default()
     };
-    let mut options = Options::default();
-    options.color_output = false;
-    options.file_prefix = true;
 
     output_buffer.append_line(&options, "-", line);
-    assert_eq!(output_buffer.buffer, "(standard input):\toutput line\n");
+    assert_eq!(output_buffer.writer.as_str(), "(standard input):output line\n");
+}
+
+#[test]
+fn append_null_separated_line_to_buffer() {
+    let line = "output line";
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+    let mut options = Options {
+        color_output: true,
+        file_prefix: true,
+        null_after_name: true,
+        ..Default::default()
+    };
+
+    output_buffer.append_line(&options, "-", line);
+    assert_eq!(output_buffer.writer.as_str(), "\u{1b}[32m\u{1b}[K(standard input)\u{1b}[0m\u{1b}[K\0output line\n");
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+    options.color_output = false;
+
+    output_buffer.append_terminated_line(&options, "path", None, ":", line, "\0");
+    assert_eq!(output_buffer.writer.as_str(), "path\0output line\0");
 }
 
 #[test]
 fn flush_buffer() {
     let line = "output_line\n";
-    let mut output_buffer = OutputBuffer {
-        buffer: String::with_capacity(BUFFER_SIZE),
-        destination: Box::new(Vec::<u8>::new())
-    };
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
 
     output_buffer.append_line(&Options::default(), "-", line);
 
     output_buffer.write_and_flush();
-    assert_eq!(output_buffer.buffer, "");
+    assert_eq!(output_buffer.writer.as_str(), "");
 }
 
 #[test]
 fn appends_line_count_to_buffer() {
     let matching_lines: usize = 10;
-    let mut output_buffer = OutputBuffer {
-        buffer: String::with_capacity(BUFFER_SIZE),
-        destination: Box::new(Vec::<u8>::new())
-    };
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
 
     let test_source = Source {
         path: String::new(),
         data: String::new(),
+        bytes: None,
     };
 
     output_buffer.append_source_counts(&Options::default(), &test_source, matching_lines);
 
-    assert_eq!(output_buffer.buffer, "10\n");
+    assert_eq!(output_buffer.writer.as_str(), "10\n");
+}
+
+#[test]
+fn appends_source_counts_at_end_of_source() {
+    let mut source = Source::new(String::from("res/test/haiku.txt"));
+    let options = Options {
+        count_lines: true,
+        patterns: vec!(String::from("dew")),
+        ..Default::default()
+    };
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    source.read_data().unwrap();
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+
+    assert_eq!(output_buffer.writer.as_str(), "2\n");
 }
 
 #[test]
 fn appends_source_matches_to_buffer() {
     let mut source = Source::new(String::from("res/test/haiku.txt"));
-    let mut options = Options::default();
+    let options = Options {
+        color_output: true,
+        patterns: vec!(String::from("dew")),
+        ..Default::default()
+    };
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    source.read_data().unwrap();
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+
+    let expected_buffer = String::from("This world of \x1b[1;33m\x1b[Kdew\x1b[0m\x1b[K,\nis a world of \x1b[1;33m\x1b[Kdew\x1b[0m\x1b[K,\n");
+
+    assert_eq!(output_buffer.writer.as_str(), expected_buffer);
+}
+#[test]
+fn appends_replaced_matches_to_buffer() {
+    let mut source = Source::new(String::from("res/test/haiku.txt"));
+    let mut options = Options {
+        color_output: false,
+        patterns: vec!(String::from("(d)(?P<rest>ew)")),
+        replace: Some(String::from("${rest}$1")),
+        ..Default::default()
+    };
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    source.read_data().unwrap();
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+
+    assert_eq!(output_buffer.writer.as_str(), "This world of ewd,\nis a world of ewd,\n");
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
     options.color_output = true;
-    options.patterns = vec!(String::from("dew"));
+
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+
+    let expected_buffer = String::from("This world of \x1b[1;33m\x1b[Kewd\x1b[0m\x1b[K,\nis a world of \x1b[1;33m\x1b[Kewd\x1b[0m\x1b[K,\n");
+
+    assert_eq!(output_buffer.writer.as_str(), expected_buffer);
+}
+
+#[test]
+fn appends_only_matching_to_buffer() {
+    let mut source = Source::new(String::from("res/test/haiku.txt"));
+    let mut options = Options {
+        color_output: false,
+        file_prefix: true,
+        only_matching: true,
+        patterns: vec!(String::from("yet|dew")),
+        ..Default::default()
+    };
 
     let regex: Regex = matcher::build_regex(&options).unwrap();
 
     source.read_data().unwrap();
 
-    let source_matches = matcher::search_lines(&regex, &source.data);
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+
+    let expected_buffer = String::from(
+        "res/test/haiku.txt:dew\nres/test/haiku.txt:dew\nres/test/haiku.txt:yet\nres/test/haiku.txt:yet\n"
+    );
 
-    let mut output_buffer = OutputBuffer {
-        buffer: String::with_capacity(BUFFER_SIZE),
-        destination: Box::new(Vec::<u8>::new())
+    assert_eq!(output_buffer.writer.as_str(), expected_buffer);
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+    options.file_prefix = false;
+    options.replace = Some(String::from("<$0>"));
+
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+
+    assert_eq!(output_buffer.writer.as_str(), "<dew>\n<dew>\n<yet>\n<yet>\n");
+}
+
+#[test]
+fn appends_multiline_matches_to_buffer() {
+    let mut source = Source::new(String::from("res/test/haiku.txt"));
+    let mut options = Options {
+        color_output: true,
+        multiline: true,
+        patterns: vec!(String::from(r"dew,\nis")),
+        ..Default::default()
+    };
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    source.read_data().unwrap();
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+
+    let expected_buffer = String::from("This world of \x1b[1;33m\x1b[Kdew,\x1b[0m\x1b[K\n\x1b[1;33m\x1b[Kis\x1b[0m\x1b[K a world of dew,\n");
+
+    assert_eq!(output_buffer.writer.as_str(), expected_buffer);
+
+    // lines covered by a replaced match are part of its replacement
+    let source = Source {
+        path: String::from("-"),
+        data: String::from("a\nb\n\nb\nc\n"),
+        bytes: None,
+    };
+    options.color_output = false;
+    options.patterns = vec!(String::from(r"a\nb\n\nb"));
+    options.replace = Some(String::from("X"));
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+
+    assert_eq!(output_buffer.writer.as_str(), "X\n");
+}
+
+#[test]
+fn appends_crlf_lines_to_buffer() {
+    let source = Source {
+        path: String::from("-"),
+        data: String::from("This world of dew,\r\nand yet, and yet.\r\n"),
+        bytes: None,
+    };
+    let options = Options {
+        color_output: false,
+        line_match: true,
+        patterns: vec!(String::from("This world of dew,")),
+        ..Default::default()
+    };
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+
+    assert_eq!(output_buffer.writer.as_str(), "This world of dew,\r\n");
+}
+
+#[test]
+fn no_erase_and_reverse_colors() {
+    let source = Source {
+        path: String::from("-"),
+        data: String::from("This world of dew,\nand yet, and yet."),
+        bytes: None,
+    };
+    let mut options = Options {
+        color_output: true,
+        invert_match: true,
+        patterns: vec!(String::from("dew")),
+        ..Default::default()
     };
+    options.colors.selected_line = String::from("1");
+    options.colors.context_line = String::from("2");
+    options.colors.no_erase = true;
 
-    output_buffer.append_source_matches(&options, &source, source_matches);
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+
+    assert_eq!(output_buffer.writer.as_str(), "\x1b[1mand yet, and yet.\x1b[0m\n");
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+    options.colors.reverse = true;
+
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+
+    assert_eq!(output_buffer.writer.as_str(), "\x1b[2mand yet, and yet.\x1b[0m\n");
+}
+
+#[test]
+fn appends_hyperlinked_file_prefix() {
+    let mut source = Source::new(String::from("res/test/haiku.txt"));
+    let mut options = Options {
+        color_output: true,
+        file_prefix: true,
+        ..Default::default()
+    };
+    options.colors.file_name = String::new();
+    options.colors.selected_match = String::new();
+    options.colors.separator = String::new();
+    options.hyperlink_format = Some(String::from("editor://{path}:{line}:{column}"));
+    options.patterns = vec!(String::from("yet"));
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    source.read_data().unwrap();
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
 
-    let expected_buffer = String::from("This world of \x1b[1;33mdew\x1b[0m,\nis a world of \x1b[1;33mdew\x1b[0m,\n");
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
 
-    assert_eq!(output_buffer.buffer, expected_buffer);
-}
\ No newline at end of file
+    let path = std::fs::canonicalize("res/test/haiku.txt").unwrap();
+    let expected_buffer = format!(
+        "\x1b]8;;editor://{}:3:5\x1b\\res/test/haiku.txt\x1b]8;;\x1b\\:and yet, and yet.\n",
+        path.to_str().unwrap()
+    );
+
+    assert_eq!(output_buffer.writer.as_str(), expected_buffer);
+
+    // hyperlinks are only written with colored output
+    options.color_output = false;
+    output_buffer.writer.clear();
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+
+    assert_eq!(output_buffer.writer.as_str(), "res/test/haiku.txt:and yet, and yet.\n");
+}
+
+#[test]
+fn resolves_hyperlink_path_per_source() {
+    let source = Source::new(String::from("res/test/haiku.txt"));
+    let mut options = Options {
+        color_output: true,
+        hyperlink_format: Some(String::from("file://{path}")),
+        ..Default::default()
+    };
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    output_buffer.begin_source(&options, &source);
+    assert_eq!(output_buffer.hyperlink_path, hyperlink::resolve_path("res/test/haiku.txt"));
+    assert!(output_buffer.hyperlink_path.is_some());
+
+    output_buffer.begin_source(&options, &Source::new(String::from("-")));
+    assert_eq!(output_buffer.hyperlink_path, None);
+
+    options.color_output = false;
+    output_buffer.begin_source(&options, &source);
+    assert_eq!(output_buffer.hyperlink_path, None);
+}
+
+#[test]
+fn appends_headings_to_buffer() {
+    let source = Source {
+        path: String::from("first.txt"),
+        data: String::from("alpha\nbeta\nalphabet\n"),
+        bytes: None,
+    };
+    let other_source = Source {
+        path: String::from("second.txt"),
+        data: String::from("gamma\nalpha\n"),
+        bytes: None,
+    };
+    let no_match_source = Source {
+        path: String::from("third.txt"),
+        data: String::from("delta\n"),
+        bytes: None,
+    };
+
+    let mut options = Options {
+        color_output: false,
+        file_prefix: true,
+        heading: true,
+        patterns: vec!(String::from("alpha")),
+        ..Default::default()
+    };
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+    matcher::search_source(&regex, &options, &no_match_source, &mut output_buffer);
+    matcher::search_source(&regex, &options, &other_source, &mut output_buffer);
+
+    let expected_buffer = "first.txt\n1:alpha\n3:alphabet\n\nsecond.txt\n2:alpha\n";
+    assert_eq!(output_buffer.writer.as_str(), expected_buffer);
+
+    // counts keep the file name on every line
+    options.count_lines = true;
+    output_buffer.writer.clear();
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+
+    assert_eq!(output_buffer.writer.as_str(), "first.txt:2\n");
+}
+
+#[test]
+fn appends_colored_headings_to_buffer() {
+    let source = Source {
+        path: String::from("first.txt"),
+        data: String::from("beta\nalpha\n"),
+        bytes: None,
+    };
+
+    let options = Options {
+        color_output: true,
+        colors: Colors::default(),
+        heading: true,
+        patterns: vec!(String::from("alpha")),
+        ..Default::default()
+    };
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+
+    // a single source has no heading, lines are still numbered
+    let expected_buffer = "\x1b[31m\x1b[K2\x1b[0m\x1b[K\x1b[35m\x1b[K:\x1b[0m\x1b[K\x1b[1;33m\x1b[Kalpha\x1b[0m\x1b[K\n";
+    assert_eq!(output_buffer.writer.as_str(), expected_buffer);
+}
+
+#[test]
+fn appends_aligned_lines_to_buffer() {
+    let source = Source {
+        path: String::from("file.txt"),
+        data: String::from("alpha\nbeta\n"),
+        bytes: None,
+    };
+
+    let mut options = Options {
+        color_output: false,
+        file_prefix: true,
+        initial_tab: true,
+        patterns: vec!(String::from("beta")),
+        ..Default::default()
+    };
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+    assert_eq!(output_buffer.writer.as_str(), "file.txt:\tbeta\n");
+
+    options.heading = true;
+    options.field_match_separator = String::from("|");
+    output_buffer.writer.clear();
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+    assert_eq!(output_buffer.writer.as_str(), "file.txt\n   2|\tbeta\n");
+}
+
+#[test]
+fn appends_context_lines_with_context_separator() {
+    let source = Source {
+        path: String::from("file.txt"),
+        data: String::from("alpha\n"),
+        bytes: None,
+    };
+    let line = SinkLine {
+        line_number: 1,
+        byte_offset: 0,
+        text: "alpha",
+        terminator: "\n",
+        matches: Vec::new(),
+        replacements: Vec::new(),
+        continues_match: false,
+        patterns: Vec::new(),
+        match_patterns: Vec::new(),
+    };
+
+    let options = Options {
+        color_output: false,
+        file_prefix: true,
+        ..Default::default()
+    };
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    output_buffer.context_line(&options, &source, &line);
+    output_buffer.matched_line(&options, &source, &line);
+    assert_eq!(output_buffer.writer.as_str(), "file.txt-alpha\nfile.txt:alpha\n");
+}
+
+#[test]
+fn appends_vimgrep_lines_to_buffer() {
+    let mut source = Source::new(String::from("res/test/haiku.txt"));
+    let mut options = Options {
+        color_output: true,
+        heading: true,
+        vimgrep: true,
+        patterns: vec!(String::from("yet")),
+        ..Default::default()
+    };
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    source.read_data().unwrap();
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+
+    let expected_buffer = "res/test/haiku.txt:3:5:and yet, and yet.\nres/test/haiku.txt:3:14:and yet, and yet.\n";
+    assert_eq!(output_buffer.writer.as_str(), expected_buffer);
+
+    options.only_matching = true;
+    output_buffer.writer.clear();
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+
+    assert_eq!(output_buffer.writer.as_str(), "res/test/haiku.txt:3:5:yet\nres/test/haiku.txt:3:14:yet\n");
+}
+
+#[test]
+fn appends_vimgrep_line_per_multiline_match() {
+    let source = Source {
+        path: String::from("-"),
+        data: String::from("foo\nbar foo\nx\nbar\n"),
+        bytes: None,
+    };
+    let options = Options {
+        vimgrep: true,
+        multiline: true,
+        patterns: vec!(String::from("foo\\n(x\\n)?bar")),
+        ..Default::default()
+    };
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+
+    let expected_buffer = "(standard input):1:1:foo\n(standard input):2:5:bar foo\n";
+    assert_eq!(output_buffer.writer.as_str(), expected_buffer);
+}
+
+#[test]
+fn appends_match_counts_and_totals() {
+    let sources = vec!(
+        Source { path: String::from("a.txt"), data: String::from("yet yet\nno\nyet\n"), bytes: None },
+        Source { path: String::from("b.txt"), data: String::from("nothing\n"), bytes: None },
+        Source { path: String::from("c.txt"), data: String::from("yet\n"), bytes: None },
+    );
+
+    let mut options = Options {
+        color_output: false,
+        file_prefix: true,
+        patterns: vec!(String::from("yet")),
+        ..Default::default()
+    };
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    let search = |options: &Options| {
+        let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+        for source in &sources {
+            matcher::search_source(&regex, options, source, &mut output_buffer);
+        }
+
+        output_buffer.append_total(options);
+        String::from(output_buffer.writer.as_str())
+    };
+
+    options.parse_option(String::from("--count-matches")).unwrap();
+    assert_eq!(search(&options), "a.txt:3\nb.txt:0\nc.txt:1\n");
+
+    options.parse_option(String::from("--hide-zero")).unwrap();
+    assert_eq!(search(&options), "a.txt:3\nc.txt:1\n");
+
+    options.parse_option(String::from("--total")).unwrap();
+    assert_eq!(search(&options), "4\n");
+
+    options.count_matches = false;
+    assert_eq!(search(&options), "3\n");
+
+    // inverted matches count lines
+    options.count_matches = true;
+    options.invert_match = true;
+    assert_eq!(search(&options), "2\n");
+}
+
+#[test]
+fn appends_matching_patterns_to_buffer() {
+    let mut source = Source::new(String::from("res/test/haiku.txt"));
+    let mut options = Options {
+        color_output: false,
+        file_prefix: true,
+        show_pattern: Some(PatternLabel::Index),
+        patterns: vec!(String::from("dew"), String::from("world"), String::from("yet")),
+        ..Default::default()
+    };
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+    let pattern_set = matcher::PatternSet::new(&options).unwrap();
+
+    source.read_data().unwrap();
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    matcher::search_source_with_patterns(&regex, Some(&pattern_set), &options, &source, &mut output_buffer);
+
+    let expected_buffer = String::from(concat!(
+        "res/test/haiku.txt:1,2:This world of dew,\n",
+        "res/test/haiku.txt:1,2:is a world of dew,\n",
+        "res/test/haiku.txt:3:and yet, and yet.\n",
+    ));
+
+    assert_eq!(output_buffer.writer.as_str(), expected_buffer);
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+    options.file_prefix = false;
+    options.only_matching = true;
+    options.show_pattern = Some(PatternLabel::Pattern);
+
+    matcher::search_source_with_patterns(&regex, Some(&pattern_set), &options, &source, &mut output_buffer);
+
+    assert_eq!(output_buffer.writer.as_str(), "world:world\ndew:dew\nworld:world\ndew:dew\nyet:yet\nyet:yet\n");
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+    options.only_matching = false;
+    options.pattern_labels = vec!(Some(String::from("D")));
+
+    matcher::search_source_with_patterns(&regex, Some(&pattern_set), &options, &source, &mut output_buffer);
+
+    let expected_buffer = String::from(concat!(
+        "D,world:This world of dew,\n",
+        "D,world:is a world of dew,\n",
+        "yet:and yet, and yet.\n",
+    ));
+
+    assert_eq!(output_buffer.writer.as_str(), expected_buffer);
+}
+
+#[test]
+fn appends_matches_with_pattern_colors() {
+    let source = Source {
+        path: String::from("-"),
+        data: String::from("This world of dew,\nand yet, and yet."),
+        bytes: None,
+    };
+    let mut options = Options {
+        color_output: true,
+        patterns: vec!(String::from("dew"), String::from("world"), String::from("yet")),
+        pattern_palette: vec!(String::from("31"), String::from("32")),
+        pattern_colors: vec!(None, Some(String::from("34"))),
+        ..Default::default()
+    };
+    options.colors.no_erase = true;
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+    let pattern_set = matcher::PatternSet::new(&options).unwrap();
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    matcher::search_source_with_patterns(&regex, Some(&pattern_set), &options, &source, &mut output_buffer);
+
+    let expected_buffer = String::from(concat!(
+        "This \x1b[34mworld\x1b[0m of \x1b[31mdew\x1b[0m,\n",
+        "and \x1b[31myet\x1b[0m, and \x1b[31myet\x1b[0m.\n",
+    ));
+
+    assert_eq!(output_buffer.writer.as_str(), expected_buffer);
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+    options.only_matching = true;
+
+    matcher::search_source_with_patterns(&regex, Some(&pattern_set), &options, &source, &mut output_buffer);
+
+    assert_eq!(output_buffer.writer.as_str(), "\x1b[34mworld\x1b[0m\n\x1b[31mdew\x1b[0m\n\x1b[31myet\x1b[0m\n\x1b[31myet\x1b[0m\n");
+}
+
+#[test]
+fn colors_multiline_matches_with_their_pattern_color() {
+    let source = Source {
+        path: String::from("-"),
+        data: String::from("foo\nbar\n"),
+        bytes: None,
+    };
+    let mut options = Options {
+        color_output: true,
+        multiline: true,
+        patterns: vec!(String::from("zzz"), String::from("foo\\nbar")),
+        pattern_palette: vec!(String::from("34"), String::from("32")),
+        ..Default::default()
+    };
+    options.colors.no_erase = true;
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+    let pattern_set = matcher::PatternSet::new(&options).unwrap();
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    matcher::search_source_with_patterns(&regex, Some(&pattern_set), &options, &source, &mut output_buffer);
+
+    assert_eq!(output_buffer.writer.as_str(), "\x1b[32mfoo\x1b[0m\n\x1b[32mbar\x1b[0m\n");
+}
+
+#[test]
+fn appends_empty_lines_to_buffer() {
+    let source = Source {
+        path: String::from("-"),
+        data: String::from("a\n\nb\n"),
+        bytes: None,
+    };
+    let options = Options {
+        color_output: false,
+        invert_match: true,
+        patterns: vec!(String::from("x")),
+        ..Default::default()
+    };
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    let mut output_buffer = OutputBuffer::with_destination(Vec::new());
+
+    matcher::search_source(&regex, &options, &source, &mut output_buffer);
+
+    assert_eq!(output_buffer.writer.as_str(), "a\n\nb\n");
+}
@@ -0,0 +1,251 @@
+mod test;
+
+use crate::hyperlink;
+use crate::json;
+use crate::matcher;
+use crate::options;
+use crate::output;
+use crate::sink;
+use crate::source;
+
+use options::Options;
+use output::BufferedWriter;
+use regex::Regex;
+use sink::{Sink, SinkLine, SourceStats};
+use source::Source;
+use std::error::Error;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+const TOOL_URI: &str = "https://github.com/CODYJEWELLWEAVER/greprs";
+
+/// Writes search results as a single SARIF 2.1.0 log.
+///
+/// Each pattern is a rule identified by its [label](Options::pattern_label), patterns
+/// sharing a label share a rule. Every match is a result of the rule of the pattern that
+/// produced it, located by its line and column region with the matched lines as snippet.
+/// Multiline matches are a single result spanning every line they touch. Columns count
+/// unicode code points. Lines selected by `invert_match` contain no matches and produce
+/// no results.
+pub struct SarifPrinter {
+    /// Buffers the log and writes it to stdout.
+    writer: BufferedWriter,
+    /// Regexes of each pattern, used to find the rule of a match.
+    regexes: Vec<Regex>,
+    /// Ids of the rules and the patterns of each rule.
+    rules: Vec<(String, Vec<String>)>,
+    /// Index of the rule of each pattern.
+    pattern_rules: Vec<usize>,
+    /// Results collected from every source.
+    results: Vec<SarifResult>,
+}
+
+/// A single match reported in the log.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct SarifResult {
+    /// Index of the rule of the match, if the pattern that produced it is known.
+    rule_index: Option<usize>,
+    /// The pattern that produced the match, if it is known.
+    pattern: Option<String>,
+    /// Text of the match, lines of multiline matches are separated by newlines.
+    text: String,
+    /// URI of the source of the match.
+    uri: String,
+    /// Line the match starts on.
+    start_line: usize,
+    /// Column the match starts at.
+    start_column: usize,
+    /// Line the match ends on.
+    end_line: usize,
+    /// Column after the end of the match.
+    end_column: usize,
+    /// Every line touched by the match, separated by newlines.
+    snippet: String,
+}
+
+impl SarifPrinter {
+    /// Creates new instance of SarifPrinter for the patterns of `options`
+    /// with stdout as destination.
+    pub fn new(options: &Options) -> Result<SarifPrinter, Box<dyn Error>> {
+        let mut rules: Vec<(String, Vec<String>)> = Vec::new();
+        let mut pattern_rules = Vec::new();
+
+        for (index, pattern) in options.patterns.iter().enumerate() {
+            let id = options.pattern_label(index);
+
+            let rule_index = match rules.iter().position(|(rule_id, _)| rule_id == id) {
+                Some(rule_index) => rule_index,
+                None => {
+                    rules.push((String::from(id), Vec::new()));
+                    rules.len() - 1
+                },
+            };
+
+            if !rules[rule_index].1.contains(pattern) {
+                rules[rule_index].1.push(pattern.clone());
+            }
+
+            pattern_rules.push(rule_index);
+        }
+
+        Ok(SarifPrinter {
+            writer: BufferedWriter::default(),
+            regexes: matcher::build_pattern_regexes(options)?,
+            rules,
+            pattern_rules,
+            results: Vec::new(),
+        })
+    }
+
+    /// Builds the SARIF log containing every collected result.
+    fn build_log(&self) -> String {
+        let rules: Vec<String> = self.rules.iter()
+            .map(|(id, patterns)| {
+                let patterns: Vec<String> = patterns.iter()
+                    .map(|pattern| format!(r#"\"{}\""#, json::escape_json(pattern)))
+                    .collect();
+                let noun = if patterns.len() == 1 { "pattern" } else { "patterns" };
+
+                format!(
+                    r#"{{"id":"{}","shortDescription":{{"text":"Matches of the {} {}"}}}}"#,
+                    json::escape_json(id),
+                    noun,
+                    patterns.join(", ")
+                )
+            })
+            .collect();
+
+        let results: Vec<String> = self.results.iter()
+            .map(|result| self.build_result(result))
+            .collect();
+
+        format!(
+            concat!(
+                r#"{{"$schema":"{}","version":"{}","runs":[{{"tool":{{"driver":{{"#,
+                r#""name":"greprs","informationUri":"{}","version":"{}","rules":[{}]}}}},"#,
+                r#""columnKind":"unicodeCodePoints","results":[{}]}}]}}"#
+            ),
+            SARIF_SCHEMA,
+            SARIF_VERSION,
+            TOOL_URI,
+            env!("CARGO_PKG_VERSION"),
+            rules.join(","),
+            results.join(",")
+        )
+    }
+
+    /// Builds the JSON object of a result. Results of unknown patterns have no rule.
+    fn build_result(&self, result: &SarifResult) -> String {
+        let rule = match result.rule_index {
+            Some(rule_index) => format!(
+                r#""ruleId":"{}","ruleIndex":{},"#,
+                json::escape_json(&self.rules[rule_index].0),
+                rule_index
+            ),
+            None => String::new(),
+        };
+
+        let message = match &result.pattern {
+            Some(pattern) => format!(
+                r#"Match of the pattern \"{}\": \"{}\""#,
+                json::escape_json(pattern),
+                json::escape_json(&result.text)
+            ),
+            None => format!(r#"Match: \"{}\""#, json::escape_json(&result.text)),
+        };
+
+        format!(
+            concat!(
+                r#"{{{}"level":"warning","message":{{"text":"{}"}},"#,
+                r#""locations":[{{"physicalLocation":{{"artifactLocation":{{"uri":"{}"}},"#,
+                r#""region":{{"startLine":{},"startColumn":{},"endLine":{},"endColumn":{},"#,
+                r#""snippet":{{"text":"{}"}}}}}}}}]}}"#
+            ),
+            rule,
+            message,
+            json::escape_json(&result.uri),
+            result.start_line,
+            result.start_column,
+            result.end_line,
+            result.end_column,
+            json::escape_json(&result.snippet)
+        )
+    }
+
+    /// Builds a result for the match starting at `index` of `line`.
+    fn start_result(&self, options: &Options, source: &Source, line: &SinkLine, index: usize) -> SarifResult {
+        let span = &line.matches[index];
+        let pattern = matcher::matching_pattern(&self.regexes, options, source, line, span);
+
+        let start_column = line.text[..span.start].chars().count() + 1;
+        let end_column = start_column + line.text[span.start..span.end].chars().count();
+
+        SarifResult {
+            rule_index: pattern.map(|pattern| self.pattern_rules[pattern]),
+            pattern: pattern.map(|pattern| options.patterns[pattern].clone()),
+            text: String::from(line.match_text(index)),
+            uri: artifact_uri(&source.path),
+            start_line: line.line_number,
+            start_column,
+            end_line: line.line_number,
+            end_column,
+            snippet: String::from(line.text),
+        }
+    }
+}
+
+impl Sink for SarifPrinter {
+    fn begin_source(&mut self, _options: &Options, _source: &Source) {}
+
+    /// Collects a result for each match starting on a selected line. Lines
+    /// continuing a multiline match extend the result of that match.
+    fn matched_line(&mut self, options: &Options, source: &Source, line: &SinkLine) {
+        // without inverting only lines inside of a multiline match have no matches
+        let inside_match = line.matches.is_empty() && options.multiline && !options.invert_match;
+
+        if line.continues_match || inside_match {
+            if let Some(result) = self.results.last_mut() {
+                result.snippet.push('\n');
+                result.snippet.push_str(line.text);
+                result.text.push('\n');
+
+                if let Some(span) = line.matches.first() {
+                    result.text.push_str(&line.text[span.start..span.end]);
+                    result.end_line = line.line_number;
+                    result.end_column = line.text[..span.end].chars().count() + 1;
+                }
+            }
+        }
+
+        let first = if line.continues_match { 1 } else { 0 };
+        for index in first..line.matches.len() {
+            let result = self.start_result(options, source, line, index);
+            self.results.push(result);
+        }
+    }
+
+    fn context_line(&mut self, _options: &Options, _source: &Source, _line: &SinkLine) {}
+
+    fn end_source(&mut self, _options: &Options, _source: &Source, _stats: &SourceStats) {}
+
+    /// Writes the SARIF log unless silent.
+    fn finish(&mut self, options: &Options) {
+        if options.silent {
+            return;
+        }
+
+        let log = self.build_log();
+        self.writer.push_str(&log);
+        self.writer.push('\n');
+        self.writer.write_and_flush();
+    }
+}
+
+/// Returns the URI that locates a source in results.
+fn artifact_uri(path: &str) -> String {
+    if path != "-" {
+        hyperlink::encode_path(path)
+    } else {
+        String::from("stdin")
+    }
+}
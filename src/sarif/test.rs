@@ -0,0 +1,143 @@
+#![allow(unused_imports)]
+use regex::Regex;
+
+use crate::matcher;
+
+use super::*;
+
+#[test]
+fn artifact_uris() {
+    assert_eq!(artifact_uri("res/test/haiku.txt"), "res/test/haiku.txt");
+    assert_eq!(artifact_uri("my file.txt"), "my%20file.txt");
+    assert_eq!(artifact_uri("-"), "stdin");
+}
+
+#[test]
+fn collects_a_result_per_match() {
+    let mut source = Source::new(String::from("res/test/haiku.txt"));
+    let options = Options {
+        patterns: vec!(String::from("yet"), String::from("dew")),
+        ..Default::default()
+    };
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    source.read_data().unwrap();
+
+    let mut printer = SarifPrinter::new(&options).unwrap();
+    printer.writer = BufferedWriter::for_test();
+
+    matcher::search_source(&regex, &options, &source, &mut printer);
+
+    assert_eq!(printer.results.len(), 4);
+    assert_eq!(
+        printer.build_result(&printer.results[0]),
+        concat!(
+            r#"{"ruleId":"dew","ruleIndex":1,"level":"warning","#,
+            r#""message":{"text":"Match of the pattern \"dew\": \"dew\""},"#,
+            r#""locations":[{"physicalLocation":{"artifactLocation":{"uri":"res/test/haiku.txt"},"#,
+            r#""region":{"startLine":1,"startColumn":15,"endLine":1,"endColumn":18,"#,
+            r#""snippet":{"text":"This world of dew,"}}}}]}"#
+        )
+    );
+    let result = printer.build_result(&printer.results[3]);
+    assert!(result.starts_with(r#"{"ruleId":"yet","ruleIndex":0,"#));
+    assert!(result.contains(r#""startLine":3,"startColumn":14,"endLine":3,"endColumn":17"#));
+}
+
+#[test]
+fn builds_sarif_log() {
+    let options = Options {
+        patterns: vec!(String::from("a\"b")),
+        ..Default::default()
+    };
+
+    let mut printer = SarifPrinter::new(&options).unwrap();
+    printer.writer = BufferedWriter::for_test();
+
+    let log = printer.build_log();
+    assert!(log.starts_with(r#"{"$schema":"https://json.schemastore.org/sarif-2.1.0.json","version":"2.1.0","runs":[{"#));
+    assert!(log.contains(r#""rules":[{"id":"a\"b","shortDescription":{"text":"Matches of the pattern \"a\"b\""}}]"#));
+    assert!(log.ends_with(r#""columnKind":"unicodeCodePoints","results":[]}]}"#));
+
+    printer.finish(&options);
+    assert_eq!(printer.writer.as_str(), "");
+}
+
+#[test]
+fn counts_columns_in_code_points() {
+    let source = Source {
+        path: String::from("-"),
+        data: String::from("ünïcode dew\n"),
+        bytes: None,
+    };
+    let options = Options {
+        patterns: vec!(String::from("dew")),
+        ..Default::default()
+    };
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    let mut printer = SarifPrinter::new(&options).unwrap();
+    printer.writer = BufferedWriter::for_test();
+
+    matcher::search_source(&regex, &options, &source, &mut printer);
+
+    let result = printer.build_result(&printer.results[0]);
+    assert!(result.contains(r#""uri":"stdin""#));
+    assert!(result.contains(r#""startColumn":9,"endLine":1,"endColumn":12"#));
+}
+
+#[test]
+fn collects_a_result_per_multiline_match() {
+    let source = Source {
+        path: String::from("-"),
+        data: String::from("foo(\n  a,\n\n  b)\nfoo(c)\n"),
+        bytes: None,
+    };
+    let options = Options {
+        multiline: true,
+        patterns: vec!(String::from(r"foo\([^)]*\)")),
+        ..Default::default()
+    };
+
+    let regex: Regex = matcher::build_regex(&options).unwrap();
+
+    let mut printer = SarifPrinter::new(&options).unwrap();
+    printer.writer = BufferedWriter::for_test();
+
+    matcher::search_source(&regex, &options, &source, &mut printer);
+
+    assert_eq!(printer.results.len(), 2);
+    assert_eq!(printer.results[0].rule_index, Some(0));
+    assert_eq!(printer.results[0].text, "foo(\n  a,\n\n  b)");
+    assert_eq!(printer.results[0].snippet, "foo(\n  a,\n\n  b)");
+    assert_eq!(
+        (printer.results[0].start_line, printer.results[0].start_column),
+        (1, 1)
+    );
+    assert_eq!(
+        (printer.results[0].end_line, printer.results[0].end_column),
+        (4, 5)
+    );
+    assert_eq!(printer.results[1].start_line, 5);
+    assert_eq!(printer.results[1].end_line, 5);
+}
+
+#[test]
+fn rules_are_identified_by_labels() {
+    let options = Options {
+        patterns: vec!(String::from("dew"), String::from("yet"), String::from("dew"), String::from("mist")),
+        pattern_labels: vec!(None, Some(String::from("weather")), None, Some(String::from("weather"))),
+        ..Default::default()
+    };
+
+    let printer = SarifPrinter::new(&options).unwrap();
+    assert_eq!(printer.pattern_rules, vec!(0, 1, 0, 1));
+
+    let log = printer.build_log();
+    assert!(log.contains(concat!(
+        r#""rules":[{"id":"dew","shortDescription":{"text":"Matches of the pattern \"dew\""}},"#,
+        r#"{"id":"weather","shortDescription":{"text":"Matches of the patterns \"yet\", \"mist\""}}]"#
+    )));
+}
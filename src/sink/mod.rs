@@ -0,0 +1,218 @@
+mod test;
+
+use crate::options;
+use crate::source;
+
+use options::Options;
+use source::Source;
+use std::io::{self, ErrorKind};
+use std::time::Duration;
+
+/// Receives the events produced while searching [sources](Source).
+///
+/// The search core ([search_source](crate::matcher::search_source)) drives a sink
+/// through the following events for each source:
+/// `begin_source`, then any number of `matched_line` and `context_line`
+/// events, then `end_source`. Once every source has been searched `finish` is called.
+///
+/// [OutputBuffer](crate::output::OutputBuffer) is the default implementation which
+/// prints results as text. Custom sinks can be used to collect results without
+/// parsing printed output.
+pub trait Sink {
+    /// Called before any lines of `source` are searched.
+    fn begin_source(&mut self, options: &Options, source: &Source);
+
+    /// Called for each selected line in `source`. Respects `invert_match`.
+    fn matched_line(&mut self, options: &Options, source: &Source, line: &SinkLine);
+
+    /// Called for each line reported as context around selected lines.
+    fn context_line(&mut self, options: &Options, source: &Source, line: &SinkLine);
+
+    /// Called after every line of `source` has been searched.
+    fn end_source(&mut self, options: &Options, source: &Source, stats: &SourceStats);
+
+    /// Called once after all sources have been searched.
+    fn finish(&mut self, options: &Options);
+
+    /// Called before `finish` with the statistics of the entire run when
+    /// [Options::stats] is set. Returns `true` if the sink reports them itself,
+    /// otherwise they are written to stderr.
+    fn report_stats(&mut self, _options: &Options, _stats: &SearchStats) -> bool {
+        false
+    }
+}
+
+/// A line of a source that is reported to a [Sink].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct SinkLine<'a> {
+    /// Line number of the line inside its source, starting from 1.
+    /// Counts records when a record separator is used.
+    pub line_number: usize,
+    /// Byte offset of the start of the line inside its source.
+    pub byte_offset: usize,
+    /// Content of the line, without its line terminator.
+    pub text: &'a str,
+    /// The terminator that followed the line in its source. Empty for a final line
+    /// without a terminator.
+    pub terminator: &'a str,
+    /// Spans of the line matched by the pattern(s).
+    pub matches: Vec<Span>,
+    /// Replacement text for each of `matches` when a replacement template is set,
+    /// see [Options::replace]. Otherwise empty.
+    pub replacements: Vec<String>,
+    /// If the first of `matches` continues a [multiline](Options::multiline) match
+    /// that started on a previous line. Every other match starts on the line.
+    pub continues_match: bool,
+    /// Indices of every pattern that matches the line when [Options::show_pattern]
+    /// or pattern colors are set, see [PatternSet](crate::matcher::PatternSet). Otherwise empty.
+    pub patterns: Vec<usize>,
+    /// Index of the pattern that produced each of `matches` when [Options::show_pattern]
+    /// or pattern colors are set, spans continuing a multiline match have the pattern of
+    /// the match they continue. Otherwise empty.
+    pub match_patterns: Vec<Option<usize>>,
+}
+
+impl SinkLine<'_> {
+    /// Returns the text that should be shown for the match at `index`,
+    /// its replacement if there is one, otherwise the matched text.
+    pub fn match_text(&self, index: usize) -> &str {
+        match self.replacements.get(index) {
+            Some(replacement) => replacement,
+            None => &self.text[self.matches[index].start..self.matches[index].end],
+        }
+    }
+
+    /// Returns the text of the line with every match replaced by its replacement.
+    pub fn replaced_text(&self) -> String {
+        let mut replaced_line = String::new();
+
+        let mut previous = 0;
+        for (index, span) in self.matches.iter().enumerate() {
+            replaced_line.push_str(&self.text[previous..span.start]);
+            replaced_line.push_str(self.match_text(index));
+            previous = span.end;
+        }
+
+        replaced_line.push_str(&self.text[previous..]);
+
+        replaced_line
+    }
+}
+
+/// Location of a match inside of a [SinkLine].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Span {
+    /// Byte offset of the start of the match, relative to the start of the line.
+    pub start: usize,
+    /// Byte offset of the end of the match, relative to the start of the line.
+    pub end: usize,
+}
+
+/// Statistics about the search of a single source.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SourceStats {
+    /// Number of selected lines. Respects `invert_match`.
+    pub matched_lines: usize,
+    /// Number of individual matches found in the source.
+    pub matches: usize,
+    /// Number of bytes searched.
+    pub bytes_searched: usize,
+}
+
+/// Statistics about an entire run, reported when [Options::stats] is set.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct SearchStats {
+    /// Number of sources searched.
+    pub searches: usize,
+    /// Number of sources with at least one selected line.
+    pub searches_with_match: usize,
+    /// Statistics summed over every searched source.
+    pub totals: SourceStats,
+    /// Number of sources skipped because they are not valid UTF-8 text.
+    pub skipped_binary: usize,
+    /// Number of sources skipped because they are directories.
+    pub skipped_ignored: usize,
+    /// Number of sources skipped because they could not be read.
+    pub skipped_errors: usize,
+    /// Wall time spent reading and searching sources.
+    pub elapsed: Duration,
+}
+
+impl SearchStats {
+    /// Adds the statistics of a searched source.
+    pub fn add_source(&mut self, stats: &SourceStats) {
+        self.searches += 1;
+        if stats.matched_lines > 0 {
+            self.searches_with_match += 1;
+        }
+
+        self.totals.matched_lines += stats.matched_lines;
+        self.totals.matches += stats.matches;
+        self.totals.bytes_searched += stats.bytes_searched;
+    }
+
+    /// Counts a source that was skipped because reading it failed with `io_err`.
+    pub fn add_skipped(&mut self, io_err: &io::Error) {
+        match io_err.kind() {
+            ErrorKind::InvalidData => self.skipped_binary += 1,
+            ErrorKind::IsADirectory => self.skipped_ignored += 1,
+            _ => self.skipped_errors += 1,
+        }
+    }
+
+    /// Returns the total number of skipped sources.
+    pub fn skipped(&self) -> usize {
+        self.skipped_binary + self.skipped_ignored + self.skipped_errors
+    }
+}
+
+/// Records the events it receives as strings, shared by the tests of sinks and
+/// of the search core.
+#[cfg(test)]
+#[derive(Default)]
+pub(crate) struct EventCollector {
+    pub events: Vec<String>,
+    /// [patterns](SinkLine::patterns) and [match_patterns](SinkLine::match_patterns)
+    /// of each selected line.
+    pub line_patterns: Vec<(Vec<usize>, Vec<Option<usize>>)>,
+}
+
+#[cfg(test)]
+impl Sink for EventCollector {
+    fn begin_source(&mut self, _options: &Options, source: &Source) {
+        self.events.push(format!("begin {}", source.path));
+    }
+
+    fn matched_line(&mut self, _options: &Options, _source: &Source, line: &SinkLine) {
+        let spans: Vec<String> = line.matches.iter()
+            .map(|span| format!("{}..{}", span.start, span.end))
+            .collect();
+
+        self.events.push(format!(
+            "match {} {} {} [{}]",
+            line.line_number,
+            line.byte_offset,
+            line.text,
+            spans.join(", ")
+        ));
+        self.line_patterns.push((line.patterns.clone(), line.match_patterns.clone()));
+    }
+
+    fn context_line(&mut self, _options: &Options, _source: &Source, line: &SinkLine) {
+        self.events.push(format!("context {}", line.line_number));
+    }
+
+    fn end_source(&mut self, _options: &Options, source: &Source, stats: &SourceStats) {
+        self.events.push(format!(
+            "end {} {} {} {}",
+            source.path,
+            stats.matched_lines,
+            stats.matches,
+            stats.bytes_searched
+        ));
+    }
+
+    fn finish(&mut self, _options: &Options) {
+        self.events.push(String::from("finish"));
+    }
+}
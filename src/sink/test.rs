@@ -0,0 +1,123 @@
+#![cfg(test)]
+#![allow(unused_imports)]
+use super::*;
+
+use crate::matcher;
+
+#[test]
+fn search_drives_sink_events() {
+    let mut source = Source::new(String::from("res/test/haiku.txt"));
+    source.read_data().unwrap();
+
+    let options = Options {
+        patterns: vec!(String::from("dew")),
+        ..Default::default()
+    };
+    let regex = matcher::build_regex(&options).unwrap();
+
+    let mut collector = EventCollector::default();
+    let stats = matcher::search_source(&regex, &options, &source, &mut collector);
+    collector.finish(&options);
+
+    assert_eq!(stats, SourceStats { matched_lines: 2, matches: 2, bytes_searched: 55 });
+    assert_eq!(collector.events, vec!(
+        String::from("begin res/test/haiku.txt"),
+        String::from("match 1 0 This world of dew, [14..17]"),
+        String::from("match 2 19 is a world of dew, [14..17]"),
+        String::from("end res/test/haiku.txt 2 2 55"),
+        String::from("finish"),
+    ));
+}
+
+#[test]
+fn inverted_search_drives_sink_events() {
+    let mut source = Source::new(String::from("res/test/haiku.txt"));
+    source.read_data().unwrap();
+
+    let options = Options {
+        patterns: vec!(String::from("dew")),
+        invert_match: true,
+        ..Default::default()
+    };
+    let regex = matcher::build_regex(&options).unwrap();
+
+    let mut collector = EventCollector::default();
+    matcher::search_source(&regex, &options, &source, &mut collector);
+
+    assert_eq!(collector.events, vec!(
+        String::from("begin res/test/haiku.txt"),
+        String::from("match 3 38 and yet, and yet. []"),
+        String::from("end res/test/haiku.txt 1 2 55"),
+    ));
+}
+
+#[test]
+fn null_data_search_drives_sink_events() {
+    let source = Source {
+        path: String::from("-"),
+        data: String::from("dew\none\0two\0dew\0"),
+        bytes: None,
+    };
+
+    let options = Options {
+        patterns: vec!(String::from("dew")),
+        null_data: true,
+        ..Default::default()
+    };
+    let regex = matcher::build_regex(&options).unwrap();
+
+    let mut collector = EventCollector::default();
+    matcher::search_source(&regex, &options, &source, &mut collector);
+
+    assert_eq!(collector.events, vec!(
+        String::from("begin -"),
+        String::from("match 1 0 dew\none [0..3]"),
+        String::from("match 3 12 dew [0..3]"),
+        String::from("end - 2 2 16"),
+    ));
+}
+
+#[test]
+fn paragraph_search_drives_sink_events() {
+    let source = Source {
+        path: String::from("-"),
+        data: String::from("one\ndew\n\ntwo\nthree\n\ndew\n"),
+        bytes: None,
+    };
+
+    let mut options = Options {
+        patterns: vec!(String::from("dew")),
+        invert_match: true,
+        ..Default::default()
+    };
+    options.parse_option(String::from("--paragraph")).unwrap();
+    let regex = matcher::build_regex(&options).unwrap();
+
+    let mut collector = EventCollector::default();
+    matcher::search_source(&regex, &options, &source, &mut collector);
+
+    assert_eq!(collector.events, vec!(
+        String::from("begin -"),
+        String::from("match 2 9 two\nthree []"),
+        String::from("end - 1 2 24"),
+    ));
+}
+
+#[test]
+fn search_stats_add_sources() {
+    let mut search_stats = SearchStats::default();
+    search_stats.add_source(&SourceStats { matched_lines: 2, matches: 3, bytes_searched: 55 });
+    search_stats.add_source(&SourceStats { matched_lines: 0, matches: 0, bytes_searched: 10 });
+    search_stats.add_skipped(&io::Error::from(ErrorKind::InvalidData));
+    search_stats.add_skipped(&io::Error::from(ErrorKind::IsADirectory));
+    search_stats.add_skipped(&io::Error::from(ErrorKind::NotFound));
+    search_stats.add_skipped(&io::Error::from(ErrorKind::PermissionDenied));
+
+    assert_eq!(search_stats.searches, 2);
+    assert_eq!(search_stats.searches_with_match, 1);
+    assert_eq!(search_stats.totals, SourceStats { matched_lines: 2, matches: 3, bytes_searched: 65 });
+    assert_eq!(search_stats.skipped_binary, 1);
+    assert_eq!(search_stats.skipped_ignored, 1);
+    assert_eq!(search_stats.skipped_errors, 2);
+    assert_eq!(search_stats.skipped(), 4);
+}
@@ -13,6 +13,9 @@ pub struct Source {
     pub path: String,
     /// Data in source path.
     pub data: String,
+    /// Bytes of a source that isn't valid UTF-8, see [read_data_lossy](Source::read_data_lossy).
+    /// `None` when `data` holds the source as it was read.
+    pub bytes: Option<Vec<u8>>,
 }
 
 impl Source {
@@ -23,6 +26,7 @@ impl Source {
         Source {
             path,
             data: String::new(),
+            bytes: None,
         }
     }
 
@@ -40,4 +44,31 @@ impl Source {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    /// Load source data, also reading sources that aren't valid UTF-8. Invalid
+    /// sequences are replaced in `data` and the bytes of the source are kept
+    /// in `bytes`.
+    pub fn read_data_lossy(&mut self) -> Result<(), Box<std::io::Error>> {
+        let mut bytes = Vec::new();
+
+        match self.path.as_str() {
+            "-" => {
+                stdin().read_to_end(&mut bytes)?;
+            },
+            _ => {
+                let mut source_file = File::open(&self.path)?;
+                source_file.read_to_end(&mut bytes)?;
+            }
+        }
+
+        match String::from_utf8(bytes) {
+            Ok(data) => self.data = data,
+            Err(err) => {
+                self.data = String::from_utf8_lossy(err.as_bytes()).into_owned();
+                self.bytes = Some(err.into_bytes());
+            }
+        }
+
+        Ok(())
+    }
+}
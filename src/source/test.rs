@@ -16,4 +16,17 @@ fn invalid_file() {
     let mut source = Source::new(String::from("file"));
     let read_result = source.read_data();
     assert!(read_result.is_err());
-}
\ No newline at end of file
+}
+
+#[test]
+fn load_source_bytes() {
+    let mut source = Source::new(String::from("res/test/haiku.txt"));
+    source.read_data_lossy().unwrap();
+    assert_eq!(source.data, "This world of dew,\nis a world of dew,\nand yet, and yet.");
+    assert_eq!(source.bytes, None);
+
+    let mut source = Source::new(String::from("res/test/latin1.txt"));
+    source.read_data_lossy().unwrap();
+    assert_eq!(source.data, "caf\u{fffd} dew\nplain dew\n");
+    assert_eq!(source.bytes, Some(b"caf\xe9 dew\nplain dew\n".to_vec()));
+}
@@ -0,0 +1,156 @@
+mod test;
+
+use crate::matcher;
+use crate::options;
+use crate::output;
+use crate::sink;
+use crate::source;
+
+use options::Options;
+use output::BufferedWriter;
+use regex::Regex;
+use sink::{Sink, SinkLine, SourceStats};
+use source::Source;
+use std::collections::HashMap;
+use std::error::Error;
+
+
+/// Counts how often each distinct match, or capture group of a match, occurs
+/// across all sources and writes a frequency table once searching finishes.
+///
+/// Counts are aggregated while searching, so only distinct values are kept in
+/// memory. Rows are sorted by descending count, then by value, and limited to
+/// [top](Options::top) rows when set. Replacements are counted instead of
+/// matches when a replacement template is set.
+pub struct Tally {
+    /// Buffers the frequency table and writes it to stdout.
+    writer: BufferedWriter,
+    /// Regex of the search, used for capture groups.
+    regex: Regex,
+    /// Capture group that is counted, the entire match when `None`.
+    group: Option<String>,
+    /// Number of occurrences of each distinct value.
+    counts: HashMap<String, usize>,
+}
+
+impl Tally {
+    /// Creates new instance of Tally with stdout as destination. `group` is the
+    /// number or name of the capture group to count, the entire match is counted
+    /// when it is empty. Fails if `regex` has no such group.
+    pub fn new(group: &str, regex: Regex) -> Result<Tally, Box<dyn Error>> {
+        let group = Self::check_group(group, &regex)?;
+
+        Ok(Tally {
+            writer: BufferedWriter::default(),
+            regex,
+            group,
+            counts: HashMap::new(),
+        })
+    }
+
+    /// Checks that `group` names a capture group of `regex`.
+    fn check_group(group: &str, regex: &Regex) -> Result<Option<String>, Box<dyn Error>> {
+        if group.is_empty() {
+            return Ok(None);
+        }
+
+        let exists = match group.parse::<usize>() {
+            Ok(index) => index < regex.captures_len(),
+            Err(_) => regex.capture_names().flatten().any(|name| name == group),
+        };
+
+        if !exists {
+            return Err(Box::from(format!("Pattern has no capture group {} to tally!", group)));
+        }
+
+        Ok(Some(String::from(group)))
+    }
+
+    /// Returns distinct values and their counts, most frequent first.
+    pub fn sorted_counts(&self) -> Vec<(&str, usize)> {
+        let mut counts: Vec<(&str, usize)> = self.counts.iter()
+            .map(|(value, count)| (value.as_str(), *count))
+            .collect();
+
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+        counts
+    }
+
+    /// Builds the frequency table, with counts right aligned. Newlines of multiline
+    /// matches are escaped so every value takes a single row.
+    fn build_table(&self, options: &Options) -> String {
+        let mut counts = self.sorted_counts();
+
+        if let Some(top) = options.top {
+            counts.truncate(top);
+        }
+
+        let width = counts.first().map_or(0, |(_, count)| count.to_string().len());
+
+        counts.iter()
+            .map(|(value, count)| format!(
+                "{:>width$} {}{}",
+                count,
+                value.replace('\n', "\\n"),
+                options.line_terminator(),
+                width = width
+            ))
+            .collect()
+    }
+}
+
+impl Sink for Tally {
+    fn begin_source(&mut self, _options: &Options, _source: &Source) {}
+
+    /// Counts every match of the line, or the capture group of every match.
+    /// Multiline matches are counted once, on the line they start on.
+    fn matched_line(&mut self, options: &Options, source: &Source, line: &SinkLine) {
+        for (index, span) in line.matches.iter().enumerate() {
+            if index == 0 && line.continues_match {
+                continue;
+            }
+
+            let value = match &self.group {
+                None if options.multiline && line.replacements.is_empty() => {
+                    let captures = matcher::match_captures(&self.regex, options, source, line, span);
+
+                    match captures.and_then(|captures| captures.get(0)) {
+                        Some(match_obj) => String::from(match_obj.as_str()),
+                        None => continue,
+                    }
+                },
+                None => String::from(line.match_text(index)),
+                Some(group) => {
+                    let captures = matcher::match_captures(&self.regex, options, source, line, span);
+                    let group = captures.as_ref().and_then(|captures| match group.parse::<usize>() {
+                        Ok(index) => captures.get(index),
+                        Err(_) => captures.name(group),
+                    });
+
+                    match group {
+                        Some(group) => String::from(group.as_str()),
+                        None => continue,
+                    }
+                },
+            };
+
+            *self.counts.entry(value).or_insert(0) += 1;
+        }
+    }
+
+    fn context_line(&mut self, _options: &Options, _source: &Source, _line: &SinkLine) {}
+
+    fn end_source(&mut self, _options: &Options, _source: &Source, _stats: &SourceStats) {}
+
+    /// Writes the frequency table unless silent.
+    fn finish(&mut self, options: &Options) {
+        if options.silent {
+            return;
+        }
+
+        let table = self.build_table(options);
+        self.writer.push_str(&table);
+        self.writer.write_and_flush();
+    }
+}
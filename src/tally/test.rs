@@ -0,0 +1,107 @@
+#![allow(unused_imports)]
+use crate::matcher;
+
+use super::*;
+
+#[test]
+fn check_groups() {
+    let regex = Regex::new("(?P<key>\\w+)=(\\d+)").unwrap();
+
+    assert_eq!(Tally::check_group("", &regex).unwrap(), None);
+    assert_eq!(Tally::check_group("key", &regex).unwrap(), Some(String::from("key")));
+    assert_eq!(Tally::check_group("2", &regex).unwrap(), Some(String::from("2")));
+    assert!(Tally::check_group("3", &regex).is_err());
+    assert!(Tally::check_group("value", &regex).is_err());
+}
+
+#[test]
+fn tallies_matches_and_groups() {
+    let source = Source {
+        path: String::from("log.txt"),
+        data: String::from("GET /a 200\nPOST /b 500\nGET /c 200\nGET /a 404\nPUT /a\n"),
+        bytes: None,
+    };
+
+    let mut options = Options {
+        patterns: vec!(String::from("(?P<method>[A-Z]+) /(\\w)(?: (?P<status>\\d+))?")),
+        ..Default::default()
+    };
+
+    let regex = matcher::build_regex(&options).unwrap();
+
+    let mut tally = Tally {
+        writer: BufferedWriter::for_test(),
+        regex: regex.clone(),
+        group: Some(String::from("method")),
+        counts: HashMap::new(),
+    };
+
+    matcher::search_source(&regex, &options, &source, &mut tally);
+    assert_eq!(tally.sorted_counts(), vec!(("GET", 3), ("POST", 1), ("PUT", 1)));
+
+    tally.counts.clear();
+    tally.group = Some(String::from("status"));
+    matcher::search_source(&regex, &options, &source, &mut tally);
+    assert_eq!(tally.sorted_counts(), vec!(("200", 2), ("404", 1), ("500", 1)));
+
+    tally.counts.clear();
+    tally.group = Some(String::from("2"));
+    matcher::search_source(&regex, &options, &source, &mut tally);
+    assert_eq!(tally.build_table(&options), "3 a\n1 b\n1 c\n");
+
+    options.top = Some(1);
+    assert_eq!(tally.build_table(&options), "3 a\n");
+}
+
+#[test]
+fn tallies_multiline_matches_once() {
+    let source = Source {
+        path: String::from("log.txt"),
+        data: String::from("foo\nbar\nfoo\nbar\n"),
+        bytes: None,
+    };
+
+    let options = Options {
+        patterns: vec!(String::from("foo\\nbar")),
+        multiline: true,
+        ..Default::default()
+    };
+
+    let regex = matcher::build_regex(&options).unwrap();
+
+    let mut tally = Tally {
+        writer: BufferedWriter::for_test(),
+        regex: regex.clone(),
+        group: None,
+        counts: HashMap::new(),
+    };
+
+    matcher::search_source(&regex, &options, &source, &mut tally);
+    assert_eq!(tally.sorted_counts(), vec!(("foo\nbar", 2)));
+    assert_eq!(tally.build_table(&options), "2 foo\\nbar\n");
+}
+
+#[test]
+fn aligns_counts() {
+    let options = Options {
+        patterns: vec!(String::from("\\w+")),
+        ..Default::default()
+    };
+
+    let regex = matcher::build_regex(&options).unwrap();
+    let source = Source {
+        path: String::from("-"),
+        data: "a ".repeat(12) + "b c\n",
+        bytes: None,
+    };
+
+    let mut tally = Tally {
+        writer: BufferedWriter::for_test(),
+        regex: regex.clone(),
+        group: None,
+        counts: HashMap::new(),
+    };
+
+    matcher::search_source(&regex, &options, &source, &mut tally);
+    assert_eq!(tally.build_table(&options), "12 a\n 1 b\n 1 c\n");
+}
@@ -0,0 +1,272 @@
+mod test;
+
+use crate::colors;
+use crate::matcher;
+use crate::options;
+use crate::output;
+use crate::sink;
+use crate::source;
+
+use colors::Colors;
+use options::Options;
+use output::OutputBuffer;
+use regex::Regex;
+use sink::{Sink, SinkLine, SourceStats};
+use source::Source;
+use std::error::Error;
+
+/// #### A template results are written with.
+///
+/// Placeholders in braces are replaced for every match:
+/// *   `{path}`: path of the source, `(standard input)` for stdin.
+/// *   `{line}`: line number of the match.
+/// *   `{col}` or `{column}`: 1-based byte column of the match.
+/// *   `{byte}` or `{offset}`: byte offset of the match inside its source.
+/// *   `{text}`: the entire line, with replacements applied.
+/// *   `{match}`: the matched text, or its replacement.
+/// *   `{1}`, `{name}`: numbered or named capture groups, empty if they didn't participate.
+///
+/// A color can be given after a colon, e.g. `{path:magenta,bold}`, see
+/// [parse_color_spec](Colors::parse_color_spec). Placeholders use the colors of the
+/// matching parts of regular output by default, `{text:}` disables coloring.
+/// `\n`, `\t`, `\r`, `\0`, `\\`, `\{` and `\}` are escapes.
+#[derive(PartialEq, Eq, Debug, Clone)]
+pub struct Template {
+    /// The template as it was written.
+    pub template: String,
+    /// Parts of the template in order.
+    segments: Vec<Segment>,
+}
+
+/// A part of a [Template].
+#[derive(PartialEq, Eq, Debug, Clone)]
+enum Segment {
+    /// Text written as is.
+    Literal(String),
+    /// A placeholder with an optional color overriding the default color.
+    Placeholder(Field, Option<String>),
+}
+
+/// Values placeholders can be replaced with.
+#[derive(PartialEq, Eq, Debug, Clone)]
+enum Field {
+    Path,
+    Line,
+    Column,
+    ByteOffset,
+    Text,
+    Match,
+    Group(usize),
+    NamedGroup(String),
+}
+
+impl Template {
+    /// Parses a template, see [Template] for the syntax.
+    pub fn parse(template: &str) -> Result<Template, Box<dyn Error>> {
+        let mut segments = Vec::new();
+        let mut literal = String::new();
+        let mut chars = template.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    let escaped = match chars.next() {
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('r') => '\r',
+                        Some('0') => '\0',
+                        Some(c @ ('\\' | '{' | '}')) => c,
+                        Some(c) => return Err(Box::from(format!("Unknown escape in template: \\{}", c))),
+                        None => return Err(Box::from("Template ends with an incomplete escape!")),
+                    };
+
+                    literal.push(escaped);
+                },
+                '{' => {
+                    let mut placeholder = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => placeholder.push(c),
+                            None => return Err(Box::from(format!("Unclosed placeholder in template: {{{}", placeholder))),
+                        }
+                    }
+
+                    if !literal.is_empty() {
+                        segments.push(Segment::Literal(std::mem::take(&mut literal)));
+                    }
+
+                    segments.push(Self::parse_placeholder(&placeholder)?);
+                },
+                '}' => return Err(Box::from("Unmatched } in template, use \\} for a literal brace!")),
+                c => literal.push(c),
+            }
+        }
+
+        if !literal.is_empty() {
+            segments.push(Segment::Literal(literal));
+        }
+
+        Ok(Template { template: String::from(template), segments })
+    }
+
+    /// Checks if the template contains at least one placeholder.
+    pub fn has_placeholders(&self) -> bool {
+        self.segments.iter().any(|segment| matches!(segment, Segment::Placeholder(..)))
+    }
+
+    /// Parses the contents of a placeholder, a name optionally followed by a color.
+    fn parse_placeholder(placeholder: &str) -> Result<Segment, Box<dyn Error>> {
+        let (name, color) = match placeholder.split_once(':') {
+            Some((name, spec)) => {
+                let color = Colors::parse_color_spec(spec)
+                    .ok_or_else(|| format!("Invalid color in template placeholder: {}", placeholder))?;
+                (name, Some(color))
+            },
+            None => (placeholder, None),
+        };
+
+        let field = match name {
+            "path" => Field::Path,
+            "line" => Field::Line,
+            "col" | "column" => Field::Column,
+            "byte" | "offset" => Field::ByteOffset,
+            "text" => Field::Text,
+            "match" => Field::Match,
+            _ if !name.is_empty() && name.chars().all(|c| c.is_ascii_digit()) => Field::Group(name.parse()?),
+            _ if !name.is_empty() && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') => {
+                Field::NamedGroup(String::from(name))
+            },
+            _ => return Err(Box::from(format!("Unknown template placeholder: {{{}}}", placeholder))),
+        };
+
+        Ok(Segment::Placeholder(field, color))
+    }
+
+    /// Renders the template for the match at `index` of `line`, or for the line
+    /// itself when it has no matches.
+    pub fn render(&self, regex: &Regex, options: &Options, source: &Source, line: &SinkLine, index: usize) -> String {
+        let span = line.matches.get(index);
+        let captures = span.and_then(|span| matcher::match_captures(regex, options, source, line, span));
+
+        let mut rendered = String::new();
+
+        for segment in &self.segments {
+            let (field, color) = match segment {
+                Segment::Literal(literal) => {
+                    rendered.push_str(literal);
+                    continue;
+                },
+                Segment::Placeholder(field, color) => (field, color),
+            };
+
+            let value = match field {
+                Field::Path => String::from(if source.path != "-" { &source.path } else { "(standard input)" }),
+                Field::Line => line.line_number.to_string(),
+                Field::Column => span.map_or(1, |span| span.start + 1).to_string(),
+                Field::ByteOffset => (line.byte_offset + span.map_or(0, |span| span.start)).to_string(),
+                Field::Text if line.replacements.is_empty() => String::from(line.text),
+                Field::Text => line.replaced_text(),
+                Field::Match => span.map_or(String::new(), |_| String::from(line.match_text(index))),
+                Field::Group(group) => Self::group_text(captures.as_ref().and_then(|captures| captures.get(*group))),
+                Field::NamedGroup(name) => Self::group_text(captures.as_ref().and_then(|captures| captures.name(name))),
+            };
+
+            if !options.color_output {
+                rendered.push_str(&value);
+                continue;
+            }
+
+            let color = match color {
+                Some(color) => color,
+                None => Self::default_color(field, &options.colors),
+            };
+
+            rendered.push_str(&OutputBuffer::apply_ansi_code(&value, color, &options.colors));
+        }
+
+        rendered
+    }
+
+    /// Returns the text of a capture group, empty if it didn't participate.
+    fn group_text(group: Option<regex::Match>) -> String {
+        group.map_or(String::new(), |group| String::from(group.as_str()))
+    }
+
+    /// Returns the color regular output uses for a field.
+    fn default_color<'c>(field: &Field, colors: &'c Colors) -> &'c str {
+        match field {
+            Field::Path => &colors.file_name,
+            Field::Line | Field::Column => &colors.line_number,
+            Field::ByteOffset => &colors.byte_offset,
+            Field::Text => &colors.selected_line,
+            Field::Match | Field::Group(_) | Field::NamedGroup(_) => &colors.selected_match,
+        }
+    }
+}
+
+/// Writes results with a [Template] through an [OutputBuffer].
+///
+/// The template is rendered once for every match, or once for selected lines
+/// without matches, followed by a line terminator. Counting and silent runs
+/// behave like the [OutputBuffer].
+pub struct TemplatePrinter {
+    /// Buffers rendered output.
+    output: OutputBuffer,
+    /// Template rendered for each match.
+    template: Template,
+    /// Regex of the search, used for capture groups.
+    regex: Regex,
+}
+
+impl TemplatePrinter {
+    /// Creates new instance of TemplatePrinter writing to stdout.
+    pub fn new(template: Template, regex: Regex) -> TemplatePrinter {
+        TemplatePrinter {
+            output: OutputBuffer::default(),
+            template,
+            regex,
+        }
+    }
+
+    /// Renders the template for every match of `line`.
+    fn append_rendered(&mut self, options: &Options, source: &Source, line: &SinkLine) {
+        for index in 0..usize::max(line.matches.len(), 1) {
+            let mut rendered = self.template.render(&self.regex, options, source, line, index);
+            rendered.push_str(options.line_terminator());
+
+            self.output.append_text(&rendered);
+        }
+    }
+}
+
+impl Sink for TemplatePrinter {
+    fn begin_source(&mut self, options: &Options, source: &Source) {
+        self.output.begin_source(options, source);
+    }
+
+    fn matched_line(&mut self, options: &Options, source: &Source, line: &SinkLine) {
+        if options.silent || options.count_lines {
+            self.output.matched_line(options, source, line);
+            return;
+        }
+
+        self.append_rendered(options, source, line);
+    }
+
+    fn context_line(&mut self, options: &Options, source: &Source, line: &SinkLine) {
+        if options.silent || options.count_lines {
+            return;
+        }
+
+        self.append_rendered(options, source, line);
+    }
+
+    fn end_source(&mut self, options: &Options, source: &Source, stats: &SourceStats) {
+        self.output.end_source(options, source, stats);
+    }
+
+    fn finish(&mut self, options: &Options) {
+        self.output.finish(options);
+    }
+}
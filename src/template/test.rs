@@ -0,0 +1,132 @@
+#![cfg(test)]
+#![allow(unused_imports)]
+use crate::matcher;
+
+use super::*;
+
+/// Renders `template` for every match of the patterns of `options` in `data`.
+fn render_all(template: &str, options: &Options, data: &str) -> Vec<String> {
+    let template = Template::parse(template).unwrap();
+    let regex = matcher::build_regex(options).unwrap();
+    let source = Source {
+        path: String::from("notes.txt"),
+        data: String::from(data),
+        bytes: None,
+    };
+
+    let mut rendered = Vec::new();
+    for (number, record) in matcher::split_records(&source.data, options).iter().enumerate() {
+        let matches = regex.find_iter(record.text)
+            .map(|match_obj| sink::Span { start: match_obj.start(), end: match_obj.end() })
+            .collect::<Vec<sink::Span>>();
+
+        let line = SinkLine {
+            line_number: number + 1,
+            byte_offset: record.byte_offset,
+            text: record.text,
+            terminator: record.terminator,
+            replacements: match &options.replace {
+                Some(replace) => matcher::expand_replacements(&regex, record.text, replace),
+                None => Vec::new(),
+            },
+            matches,
+            continues_match: false,
+            patterns: Vec::new(),
+            match_patterns: Vec::new(),
+        };
+
+        for index in 0..line.matches.len() {
+            rendered.push(template.render(&regex, options, &source, &line, index));
+        }
+    }
+
+    rendered
+}
+
+#[test]
+fn parse_templates() {
+    let template = Template::parse("{path}:{line}:{col}: {match} | {text}").unwrap();
+    assert_eq!(template.segments.len(), 9);
+    assert_eq!(template.segments[0], Segment::Placeholder(Field::Path, None));
+    assert_eq!(template.segments[1], Segment::Literal(String::from(":")));
+    assert_eq!(template.segments[7], Segment::Literal(String::from(" | ")));
+
+    let template = Template::parse("\\{{1}\\}\\t{name:green,bold}{offset:}").unwrap();
+    assert_eq!(template.segments, vec!(
+        Segment::Literal(String::from("{")),
+        Segment::Placeholder(Field::Group(1), None),
+        Segment::Literal(String::from("}\t")),
+        Segment::Placeholder(Field::NamedGroup(String::from("name")), Some(String::from("32;1"))),
+        Segment::Placeholder(Field::ByteOffset, Some(String::new())),
+    ));
+    assert!(template.has_placeholders());
+
+    assert!(!Template::parse("jsn").unwrap().has_placeholders());
+    assert!(!Template::parse("\\{path\\}").unwrap().has_placeholders());
+}
+
+#[test]
+fn parse_invalid_templates() {
+    assert!(Template::parse("{path").is_err());
+    assert!(Template::parse("path}").is_err());
+    assert!(Template::parse("{}").is_err());
+    assert!(Template::parse("{pa th}").is_err());
+    assert!(Template::parse("{path:purple}").is_err());
+    assert!(Template::parse("{path:#€}").is_err());
+    assert!(Template::parse("\\q").is_err());
+    assert!(Template::parse("trailing\\").is_err());
+}
+
+#[test]
+fn render_fields() {
+    let options = Options {
+        color_output: false,
+        patterns: vec!(String::from("(?P<key>\\w+)=(\\d+)?")),
+        ..Default::default()
+    };
+
+    let rendered = render_all(
+        "{path}:{line}:{col}:{byte}: {match} | {key} {2} | {text}",
+        &options,
+        "a=1 b=\nc=3\n"
+    );
+
+    assert_eq!(rendered, vec!(
+        String::from("notes.txt:1:1:0: a=1 | a 1 | a=1 b="),
+        String::from("notes.txt:1:5:4: b= | b  | a=1 b="),
+        String::from("notes.txt:2:1:7: c=3 | c 3 | c=3"),
+    ));
+}
+
+#[test]
+fn render_replacements() {
+    let options = Options {
+        color_output: false,
+        patterns: vec!(String::from("(\\w+)=(\\d+)")),
+        replace: Some(String::from("$2=$1")),
+        ..Default::default()
+    };
+
+    let rendered = render_all("{match} {0} {text}", &options, "a=1 b=2\n");
+
+    assert_eq!(rendered, vec!(
+        String::from("1=a a=1 1=a 2=b"),
+        String::from("2=b b=2 1=a 2=b"),
+    ));
+}
+
+#[test]
+fn render_colors() {
+    let options = Options {
+        color_output: true,
+        colors: Colors::default(),
+        patterns: vec!(String::from("dew")),
+        ..Default::default()
+    };
+
+    let rendered = render_all("{line}:{match:bright-red}:{text:}", &options, "the dew\n");
+
+    assert_eq!(rendered, vec!(String::from(
+        "\x1b[31m\x1b[K1\x1b[0m\x1b[K:\x1b[91m\x1b[Kdew\x1b[0m\x1b[K:the dew"
+    )));
+}
@@ -20,8 +20,10 @@ fn config_source_setup() {
     let mut config = Config::new(args).unwrap();
 
     let expected_source = Source::new(String::from("res/test/haiku.txt"));
-    let mut expected_options = Options::default();
-    expected_options.patterns = vec!(String::from("dew"));
+    let expected_options = Options {
+        patterns: vec!(String::from("dew")),
+        ..Default::default()
+    };
 
     assert_eq!(config, Config { sources: vec!(expected_source), options: expected_options });
 
@@ -77,15 +79,12 @@ fn invert_match() {
 
     let mut matched_line_nums: Vec<i32> = vec!();
 
-    let mut line_num = 0;
-    for (line, matches) in line_matches {
+    for (line_num, (line, matches)) in (0..).zip(line_matches) {
         let mut matches = matches.peekable();
 
-        if matches.peek().is_none() && line != "" {
+        if matches.peek().is_none() && !line.is_empty() {
             matched_line_nums.push(line_num);
         }
-
-        line_num += 1;
     }
 
     let expected_matched_lines = vec!(6, 27, 31);